@@ -1,8 +1,8 @@
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::{
-    parse_macro_input, parse_quote, punctuated::Punctuated, DeriveInput, GenericParam, Ident, Meta,
-    MetaList,
+    parse_macro_input, parse_quote, punctuated::Punctuated, DeriveInput, GenericParam, Ident,
+    Token, Type,
 };
 
 #[proc_macro_derive(Handle, attributes(callback))]
@@ -20,35 +20,35 @@ pub fn create_handle_callbacks(input: TokenStream) -> TokenStream {
     let bounds = input.generics.where_clause.clone();
     let vis = input.vis;
 
-    // A hashmap of callback field names as well as the metalist. Only fields with the `callback` attribute are included.
+    // A list of callback field names as well as their argument types. Only fields with the `callback` attribute are included.
     let output = if let syn::Data::Struct(data) = input.data {
-        let callbacks: Vec<(Ident, MetaList)> = data
+        let callbacks: Vec<(Ident, Punctuated<Type, Token![,]>)> = data
             .fields
             .iter()
             // Only get fields with callback attributes
             .filter_map(|field| {
-                let metas: Vec<Meta> = field
+                // Find (if any) the attribute with the "callback" ident. The
+                // arguments are parsed as types rather than metas so that
+                // generic types like `RangeInclusive<f32>` are supported. A
+                // bare `#[callback]` takes no arguments.
+                field
                     .attrs
                     .iter()
-                    .filter_map(|a| a.parse_meta().ok())
-                    .collect();
-                // Find (if any) the attribute with the "callback" ident
-                metas.iter().find_map(|meta| match meta {
-                    Meta::List(meta_list) => {
-                        match meta_list.path == (format_ident!("callback")).into() {
-                            true => Some((field.ident.clone().unwrap(), meta_list.clone())),
-                            false => None,
-                        }
-                    }
-                    _ => None,
-                })
+                    .find(|a| a.path.is_ident("callback"))
+                    .and_then(|a| match a.tokens.is_empty() {
+                        true => Some(Punctuated::new()),
+                        false => a
+                            .parse_args_with(Punctuated::<Type, Token![,]>::parse_terminated)
+                            .ok(),
+                    })
+                    .map(|types| (field.ident.clone().unwrap(), types))
             })
             .collect();
 
         let callback_idents: Vec<Ident> =
             callbacks.iter().map(|(ident, _)| ident.clone()).collect();
-        let callback_types: Vec<Punctuated<_, _>> =
-            callbacks.iter().map(|(_, ty)| ty.nested.clone()).collect();
+        let callback_types: Vec<Punctuated<Type, Token![,]>> =
+            callbacks.iter().map(|(_, ty)| ty.clone()).collect();
 
         quote! {
             #vis trait #id #generics #bounds
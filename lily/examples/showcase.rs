@@ -1,6 +1,6 @@
 use lily::{
     math::Vec2,
-    util::{CurvePoint, CurvePoints},
+    util::{parse_value, CurvePoint, CurvePoints, Parameter, Viewport},
     widgets::*,
     DEFAULT_STYLE,
 };
@@ -11,15 +11,21 @@ use vizia::prelude::*;
 pub struct AppData {
     xy_data: Vec2,
     mseg_data: CurvePoints,
-    mseg_zoom_data: RangeInclusive<f32>,
+    mseg_zoom_data: Viewport,
+    waveform: usize,
+    knob: f32,
+    octave: usize,
 }
 
 impl Default for AppData {
     fn default() -> Self {
         Self {
             xy_data: Vec2::ZERO,
-            mseg_zoom_data: 0.0f32..=1.0f32,
-            mseg_data: CurvePoints(
+            mseg_zoom_data: Viewport::default(),
+            waveform: 0,
+            knob: 0.5,
+            octave: 2,
+            mseg_data: CurvePoints::new(
                 vec![
                     (0f32, 0f32),
                     (0.5f32, 1.0f32),
@@ -36,40 +42,69 @@ impl Default for AppData {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub enum AppEvent {
     XyControl { point: Vec2 },
     MsegZoomStart { value: f32 },
     MsegZoomEnd { value: f32 },
+    MsegZoom(RangeInclusive<f32>),
+    MsegZoomY(RangeInclusive<f32>),
     MsegPoint { index: usize, pos: Vec2 },
+    MsegPoints(Vec<(usize, Vec2)>),
     MsegInsertPoint { index: usize, pos: Vec2 },
     MsegRemovePoint { index: usize },
+    MsegCurve { index: usize, curve: f32 },
+    MsegRestore(CurvePoints),
+    Waveform(usize),
+    Knob(f32),
+    Octave(usize),
 }
 
 impl Model for AppData {
     fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
-        event.map(|ev: &AppEvent, _| match *ev {
+        event.map(|ev: &AppEvent, _| match ev {
             AppEvent::XyControl { point } => {
-                self.xy_data = point;
+                self.xy_data = *point;
             }
             AppEvent::MsegZoomStart { value } => {
-                self.mseg_zoom_data = value..=*self.mseg_zoom_data.end()
+                self.mseg_zoom_data.x = *value..=*self.mseg_zoom_data.x.end()
             }
             AppEvent::MsegZoomEnd { value } => {
-                self.mseg_zoom_data = *self.mseg_zoom_data.start()..=value
+                self.mseg_zoom_data.x = *self.mseg_zoom_data.x.start()..=*value
             }
+            AppEvent::MsegZoom(range) => self.mseg_zoom_data.x = range.clone(),
+            AppEvent::MsegZoomY(range) => self.mseg_zoom_data.y = range.clone(),
             AppEvent::MsegPoint { index, pos } => {
-                if let Some(p) = self.mseg_data.get_mut(index) {
+                if let Some(p) = self.mseg_data.get_mut(*index) {
                     p.x = pos.x;
                     p.y = pos.y
                 }
             }
+            AppEvent::MsegPoints(points) => {
+                for (index, pos) in points {
+                    if let Some(p) = self.mseg_data.get_mut(*index) {
+                        p.x = pos.x;
+                        p.y = pos.y
+                    }
+                }
+            }
             AppEvent::MsegInsertPoint { index, pos } => {
-                self.mseg_data.insert(index, CurvePoint::from(pos));
+                self.mseg_data.insert_point(*index, CurvePoint::from(*pos));
             }
             AppEvent::MsegRemovePoint { index } => {
-                self.mseg_data.remove(index);
+                self.mseg_data.remove_point(*index);
+            }
+            AppEvent::MsegCurve { index, curve } => {
+                if let Some(p) = self.mseg_data.get_mut(*index) {
+                    p.curve = *curve;
+                }
             }
+            AppEvent::MsegRestore(points) => {
+                self.mseg_data = points.clone();
+            }
+            AppEvent::Waveform(index) => self.waveform = *index,
+            AppEvent::Knob(value) => self.knob = *value,
+            AppEvent::Octave(index) => self.octave = *index,
         });
     }
 }
@@ -83,9 +118,10 @@ fn main() {
             HStack::new(cx, |cx| {
                 // XY Pad
                 VStack::new(cx, |cx| {
-                    // XyPad::new(cx, AppData::xy_data)
-                    //     .on_changing_point(|cx, point| cx.emit(AppEvent::XyControl { point }));
+                    XyPad::new(cx, AppData::xy_data)
+                        .on_changing_point(|cx, point| cx.emit(AppEvent::XyControl { point }));
                     DragSlider::new(cx, AppData::xy_data.map(|pos| pos.x), -1f32..=1f32)
+                        .default_value(0f32)
                         .on_changing(|cx, val| {
                             cx.emit(AppEvent::XyControl {
                                 point: Vec2::new(val, AppData::xy_data.get(cx).y),
@@ -93,6 +129,15 @@ fn main() {
                         })
                         .height(Pixels(24f32))
                         .width(Pixels(200f32));
+                    DiscreteSlider::new(cx, AppData::waveform, ["sine", "square", "saw"])
+                        .on_changing(|cx, index| cx.emit(AppEvent::Waveform(index)))
+                        .height(Pixels(24f32))
+                        .width(Pixels(200f32));
+                    // Press and point towards a value to pick it
+                    FanSlider::new(cx, AppData::knob, 0f32..=1f32)
+                        .on_changing(|cx, value| cx.emit(AppEvent::Knob(value)))
+                        .height(Pixels(12f32))
+                        .width(Pixels(48f32));
                 })
                 .width(Pixels(200f32));
                 DragSlider::new(cx, AppData::xy_data.map(|pos| pos.y), 1f32..=-1f32)
@@ -101,19 +146,61 @@ fn main() {
                             point: Vec2::new(AppData::xy_data.get(cx).x, val),
                         });
                     })
+                    // Drag anywhere along the slider without the value jumping
+                    .relative(true)
+                    .sensitivity(400f32)
                     .height(Pixels(200f32))
                     .width(Pixels(24f32))
                     .top(Percentage(0f32));
+                // Knobs
+                VStack::new(cx, |cx| {
+                    // One definition drives the knob, its label and typed values
+                    let amount = Parameter::new(0f32..=1f32)
+                        .with_default(0.5)
+                        .with_value_to_string(|value| format!("{:.0}%", value * 100f32))
+                        .with_string_to_value(|text| parse_value(text).map(|value| value / 100f32));
+                    Knob::with_param(cx, AppData::knob, amount.clone())
+                        .on_changing(|cx, value| cx.emit(AppEvent::Knob(value)));
+                    // Drag up or down, or double-click to type a value
+                    DragLabel::with_param(cx, AppData::knob, amount)
+                        .on_changing(|cx, value| cx.emit(AppEvent::Knob(value)));
+                    // A bipolar knob, modulated by the knob above
+                    Knob::with_modulation(
+                        cx,
+                        AppData::xy_data.map(|pos| pos.x),
+                        AppData::knob.map(|knob| knob * 0.5),
+                        -1f32..=1f32,
+                    )
+                    .on_changing(|cx, val| {
+                        cx.emit(AppEvent::XyControl {
+                            point: Vec2::new(val, AppData::xy_data.get(cx).y),
+                        });
+                    });
+                    DiscreteKnob::new(cx, AppData::octave, ["-2", "-1", "0", "+1", "+2"])
+                        .on_changing(|cx, index| cx.emit(AppEvent::Octave(index)))
+                        .width(Pixels(72f32))
+                        .height(Pixels(72f32));
+                })
+                .width(Auto)
+                .row_between(Pixels(12f32));
             });
             // Multi stage envelope generator
             Mseg::new(cx, AppData::mseg_data, AppData::mseg_zoom_data, 8f32)
                 .on_changing_range_start(|cx, x| cx.emit(AppEvent::MsegZoomStart { value: x }))
                 .on_changing_range_end(|cx, x| cx.emit(AppEvent::MsegZoomEnd { value: x }))
+                .on_changing_range_both(|cx, range| cx.emit(AppEvent::MsegZoom(range)))
+                .on_changing_range_y(|cx, range| cx.emit(AppEvent::MsegZoomY(range)))
                 .on_changing_point(|cx, index, pos| {
                     cx.emit(AppEvent::MsegPoint { index, pos });
                 })
+                .on_changing_points(|cx, points| cx.emit(AppEvent::MsegPoints(points)))
                 .on_insert_point(|cx, index, pos| cx.emit(AppEvent::MsegInsertPoint { index, pos }))
-                .on_remove_point(|cx, index| cx.emit(AppEvent::MsegRemovePoint { index }));
+                .on_remove_point(|cx, index| cx.emit(AppEvent::MsegRemovePoint { index }))
+                .on_changing_curve(|cx, index, curve| {
+                    cx.emit(AppEvent::MsegCurve { index, curve })
+                })
+                .on_restore_points(|cx, points| cx.emit(AppEvent::MsegRestore(points)))
+                .grid(MsegGrid::beats(120f32, 1f32).with_value_steps(4));
         })
         .background_color(Color::rgb(21, 20, 21))
         .width(Stretch(1f32))
@@ -1,3 +1,5 @@
+#[cfg(feature = "nih")]
+pub mod nih;
 pub mod util;
 pub mod widgets;
 pub use glam as math;
@@ -0,0 +1,222 @@
+//! Bindings from lily widgets to the parameters of a
+//! [nih-plug](https://github.com/robbert-vdh/nih-plug) plugin, enabled with
+//! the `nih` feature
+
+use crate::util::Parameter;
+use crate::widgets::{DragLabel, DragLabelHandle, DragSlider, DragSliderHandle, Knob, KnobHandle};
+use nih_plug::prelude::{GuiContext, Param, ParamSetter, Params};
+use std::{cell::Cell, rc::Rc, sync::Arc};
+use vizia::prelude::*;
+
+/// Builds lily widgets bound to the parameters of a plugin. Widgets work on
+/// the normalized values of the parameters, and send changes to the host
+/// through a [`ParamSetter`], wrapped in automation gestures.
+///
+/// ```ignore
+/// let binder = ParamBinder::new(Data::params, context.clone());
+/// binder.knob(cx, |params| &params.gain);
+/// binder.slider(cx, |params| &params.cutoff);
+/// ```
+///
+/// The widgets read the parameters when they are drawn, so the editor should
+/// redraw when the host changes them. Setting `on_changing`, `on_reset`,
+/// `on_drag_start` or `on_drag_end` on a bound widget replaces the binding.
+pub struct ParamBinder<L> {
+    params: L,
+    context: Arc<dyn GuiContext>,
+}
+
+impl<L, P> ParamBinder<L>
+where
+    L: Lens<Target = Arc<P>>,
+    P: Params,
+{
+    /// Create a new `ParamBinder`
+    ///
+    /// # Parameters
+    ///
+    /// * `params` - a `vizia::Lens` to the parameters of the plugin
+    /// * `context` - the `GuiContext` passed to the editor
+    pub fn new(params: L, context: Arc<dyn GuiContext>) -> Self {
+        Self { params, context }
+    }
+
+    /// Gets a [`Parameter`] over the normalized value of a parameter, which
+    /// uses the parameter's default, steps and formatting
+    pub fn parameter<T, F>(&self, cx: &Context, map: F) -> Parameter
+    where
+        T: Param,
+        F: 'static + Copy + Fn(&P) -> &T,
+    {
+        let params = self.params.get(cx);
+        let param = map(&params);
+        let default = param.preview_normalized(param.default_plain_value());
+        let mut parameter = Parameter::new(0f32..=1f32).with_default(default);
+        if let Some(steps) = param.step_count() {
+            parameter = parameter.with_step(1f32 / steps as f32);
+        }
+        let format_params = params.clone();
+        parameter
+            .with_value_to_string(move |normalized| {
+                map(&format_params).normalized_value_to_string(normalized, true)
+            })
+            .with_string_to_value(move |text| map(&params).string_to_normalized_value(text))
+    }
+
+    /// Builds a [`DragSlider`] bound to a parameter
+    pub fn slider<T, F>(
+        &self,
+        cx: &mut Context,
+        map: F,
+    ) -> Handle<DragSlider<impl Lens<Target = f32>>>
+    where
+        T: Param,
+        F: 'static + Copy + Fn(&P) -> &T,
+    {
+        let gesture = self.gesture(cx, map);
+        let parameter = self.parameter(cx, map);
+        let (start, set, end, reset) = (
+            gesture.clone(),
+            gesture.clone(),
+            gesture.clone(),
+            gesture.clone(),
+        );
+        DragSlider::with_param(cx, self.normalized(map), parameter)
+            .on_drag_start(move |_| start.begin())
+            .on_changing(move |_, normalized| set.set(normalized))
+            .on_reset(move |_, normalized| reset.set(normalized))
+            .on_drag_end(move |_| end.end())
+    }
+
+    /// Builds a [`DragLabel`] bound to a parameter, showing its value as
+    /// formatted by the plugin
+    pub fn label<T, F>(
+        &self,
+        cx: &mut Context,
+        map: F,
+    ) -> Handle<DragLabel<impl Lens<Target = f32>>>
+    where
+        T: Param,
+        F: 'static + Copy + Fn(&P) -> &T,
+    {
+        let gesture = self.gesture(cx, map);
+        let parameter = self.parameter(cx, map);
+        let (start, set, end) = (gesture.clone(), gesture.clone(), gesture.clone());
+        DragLabel::with_param(cx, self.normalized(map), parameter)
+            .on_drag_start(move |_| start.begin())
+            .on_changing(move |_, normalized| set.set(normalized))
+            .on_drag_end(move |_| end.end())
+    }
+
+    /// Builds a [`Knob`] bound to a parameter, with a ring showing how much
+    /// the host modulates it
+    pub fn knob<T, F>(
+        &self,
+        cx: &mut Context,
+        map: F,
+    ) -> Handle<Knob<impl Lens<Target = f32>, impl Lens<Target = f32>>>
+    where
+        T: Param,
+        F: 'static + Copy + Fn(&P) -> &T,
+    {
+        let gesture = self.gesture(cx, map);
+        let parameter = self.parameter(cx, map);
+        let (start, set, end, reset) = (
+            gesture.clone(),
+            gesture.clone(),
+            gesture.clone(),
+            gesture.clone(),
+        );
+        let modulation = self.params.clone().map(move |params: &Arc<P>| {
+            let param = map(params);
+            param.modulated_normalized_value() - param.unmodulated_normalized_value()
+        });
+        Knob::with_param_and_modulation(cx, self.normalized(map), modulation, parameter)
+            .on_drag_start(move |_| start.begin())
+            .on_changing(move |_, normalized| set.set(normalized))
+            .on_reset(move |_, normalized| reset.set(normalized))
+            .on_drag_end(move |_| end.end())
+    }
+
+    /// A lens to the normalized value of a parameter, without modulation
+    fn normalized<T, F>(&self, map: F) -> impl Lens<Target = f32>
+    where
+        T: Param,
+        F: 'static + Copy + Fn(&P) -> &T,
+    {
+        self.params
+            .clone()
+            .map(move |params: &Arc<P>| map(params).unmodulated_normalized_value())
+    }
+
+    fn gesture<T, F>(&self, cx: &Context, map: F) -> Gesture<P, F>
+    where
+        T: Param,
+        F: 'static + Copy + Fn(&P) -> &T,
+    {
+        Gesture {
+            context: self.context.clone(),
+            params: self.params.get(cx),
+            map,
+            active: Rc::new(Cell::new(false)),
+        }
+    }
+}
+
+/// Sends the changes a widget makes to a parameter to the host, between the
+/// start and end of a drag
+struct Gesture<P, F> {
+    context: Arc<dyn GuiContext>,
+    params: Arc<P>,
+    map: F,
+    /// Whether the widget is being dragged, shared between its callbacks
+    active: Rc<Cell<bool>>,
+}
+
+impl<P, F> Clone for Gesture<P, F>
+where
+    F: Copy,
+{
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+            params: self.params.clone(),
+            map: self.map,
+            active: self.active.clone(),
+        }
+    }
+}
+
+impl<P, T, F> Gesture<P, F>
+where
+    P: Params,
+    T: Param,
+    F: Fn(&P) -> &T,
+{
+    fn begin(&self) {
+        let setter = ParamSetter::new(self.context.as_ref());
+        setter.begin_set_parameter((self.map)(&self.params));
+        self.active.set(true);
+    }
+
+    /// Sets the normalized value. Changes outside of a drag, like typed
+    /// values and resets, are sent as a gesture of their own.
+    fn set(&self, normalized: f32) {
+        let setter = ParamSetter::new(self.context.as_ref());
+        let param = (self.map)(&self.params);
+        match self.active.get() {
+            true => setter.set_parameter_normalized(param, normalized),
+            false => {
+                setter.begin_set_parameter(param);
+                setter.set_parameter_normalized(param, normalized);
+                setter.end_set_parameter(param);
+            }
+        }
+    }
+
+    fn end(&self) {
+        let setter = ParamSetter::new(self.context.as_ref());
+        setter.end_set_parameter((self.map)(&self.params));
+        self.active.set(false);
+    }
+}
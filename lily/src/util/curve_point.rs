@@ -1,13 +1,196 @@
 use glam::Vec2;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::ops::{Deref, DerefMut};
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct CurvePoints(pub Vec<CurvePoint>);
+/// The largest magnitude a [`CurvePoint::curve`] is evaluated with. Anything
+/// past this is visually indistinguishable from a step and risks overflowing
+/// the exponential.
+pub const MAX_CURVE: f32 = 32f32;
+/// Curves with a smaller magnitude than this are evaluated as straight lines
+const LINEAR_THRESHOLD: f32 = 1e-3;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(from = "CurvePointsFormat")]
+pub struct CurvePoints {
+    pub points: Vec<CurvePoint>,
+    /// The index of the point the envelope holds at while the gate is open
+    #[serde(default)]
+    pub sustain: Option<usize>,
+    /// The points the envelope loops between while the gate is open
+    #[serde(default)]
+    pub loop_region: Option<LoopRegion>,
+}
+
+/// The formats [`CurvePoints`] can be loaded from
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CurvePointsFormat {
+    Markers {
+        points: Vec<CurvePoint>,
+        #[serde(default)]
+        sustain: Option<usize>,
+        #[serde(default)]
+        loop_region: Option<LoopRegion>,
+    },
+    /// The bare array of points saved before there were markers
+    Points(Vec<CurvePoint>),
+}
+
+impl From<CurvePointsFormat> for CurvePoints {
+    fn from(format: CurvePointsFormat) -> Self {
+        match format {
+            CurvePointsFormat::Markers {
+                points,
+                sustain,
+                loop_region,
+            } => Self {
+                points,
+                sustain,
+                loop_region,
+            },
+            CurvePointsFormat::Points(points) => Self::new(points),
+        }
+    }
+}
+
+/// A loop between two point indices, where `start` is less than `end`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoopRegion {
+    pub start: usize,
+    pub end: usize,
+}
 
 impl CurvePoints {
     pub fn new(points: Vec<CurvePoint>) -> Self {
-        Self(points)
+        Self {
+            points,
+            sustain: None,
+            loop_region: None,
+        }
+    }
+
+    /// Inserts a point at `index`, shifting the sustain and loop markers so
+    /// they stay attached to the same points
+    pub fn insert_point(&mut self, index: usize, point: CurvePoint) {
+        self.points.insert(index, point);
+        let shift = |i: usize| if i >= index { i + 1 } else { i };
+        self.sustain = self.sustain.map(shift);
+        self.loop_region = self.loop_region.map(|region| LoopRegion {
+            start: shift(region.start),
+            end: shift(region.end),
+        });
+    }
+
+    /// Removes the point at `index`. Any marker attached to the removed point
+    /// is cleared, and markers after it are shifted to stay attached to the
+    /// same points.
+    pub fn remove_point(&mut self, index: usize) -> CurvePoint {
+        let point = self.points.remove(index);
+        let shift = |i: usize| match i.cmp(&index) {
+            Ordering::Less => Some(i),
+            Ordering::Equal => None,
+            Ordering::Greater => Some(i - 1),
+        };
+        self.sustain = self.sustain.and_then(shift);
+        self.loop_region =
+            self.loop_region
+                .and_then(|region| match (shift(region.start), shift(region.end)) {
+                    (Some(start), Some(end)) if start < end => Some(LoopRegion { start, end }),
+                    _ => None,
+                });
+        point
+    }
+
+    /// The sustain point index, if it refers to an existing point
+    pub fn valid_sustain(&self) -> Option<usize> {
+        self.sustain.filter(|&i| i < self.len())
+    }
+
+    /// The loop region, if it refers to existing points and has a length
+    pub fn valid_loop_region(&self) -> Option<LoopRegion> {
+        self.loop_region.filter(|region| {
+            region.start < region.end
+                && region.end < self.len()
+                && self[region.start].x < self[region.end].x
+        })
+    }
+
+    /// The `x` of the last point, or `0` if there are no points
+    pub fn duration(&self) -> f32 {
+        self.last().map(|p| p.x).unwrap_or_default()
+    }
+
+    /// Gets the index of the segment containing `x`, where segment `i` spans
+    /// from point `i` to point `i + 1`. Returns `None` if `x` lies before the
+    /// first point or at/after the last point.
+    pub fn segment_at(&self, x: f32) -> Option<usize> {
+        // The number of points at or before `x`
+        let i = self.partition_point(|p| p.x <= x);
+        if i == 0 || i >= self.len() {
+            None
+        } else {
+            Some(i - 1)
+        }
+    }
+
+    /// Evaluates segment `index` at `x`, using the curve of the point that
+    /// ends the segment. `x` is clamped to the segment.
+    pub fn segment_value(&self, index: usize, x: f32) -> f32 {
+        match (self.get(index), self.get(index + 1)) {
+            (Some(left), Some(right)) => {
+                let width = right.x - left.x;
+                let t = if width > 0f32 {
+                    ((x - left.x) / width).clamp(0f32, 1f32)
+                } else {
+                    1f32
+                };
+                left.y + (right.y - left.y) * right.shape(t)
+            }
+            (Some(left), None) => left.y,
+            _ => 0f32,
+        }
+    }
+
+    /// Evaluates the envelope at `x` seconds. Values before the first point
+    /// and after the last point hold the first and last `y` respectively.
+    pub fn value_at(&self, x: f32) -> f32 {
+        match self.segment_at(x) {
+            Some(index) => self.segment_value(index, x),
+            None => match (self.first(), self.last()) {
+                (Some(first), _) if x < first.x => first.y,
+                (_, Some(last)) => last.y,
+                _ => 0f32,
+            },
+        }
+    }
+
+    /// Renders the envelope into `buffer`, one value per sample, starting at
+    /// `start` seconds. Segments are walked incrementally, so this is cheaper
+    /// than calling [`CurvePoints::value_at`] for every sample.
+    pub fn render(&self, start: f32, sample_rate: f32, buffer: &mut [f32]) {
+        let step = 1f32 / sample_rate;
+        // Before the first point, start at the first segment, which holds the
+        // first `y` until the segment begins
+        let mut segment = match self.first() {
+            Some(first) if start < first.x => (self.len() > 1).then_some(0),
+            _ => self.segment_at(start),
+        };
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let x = start + i as f32 * step;
+            // Advance through any segments we have passed
+            while let Some(index) = segment {
+                if x < self[index + 1].x {
+                    break;
+                }
+                segment = (index + 2 < self.len()).then_some(index + 1);
+            }
+            *sample = match (segment, self.last()) {
+                (Some(index), _) => self.segment_value(index, x),
+                (None, Some(last)) => last.y,
+                (None, None) => 0f32,
+            };
+        }
     }
 }
 
@@ -15,25 +198,48 @@ impl Deref for CurvePoints {
     type Target = Vec<CurvePoint>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.points
     }
 }
 
 impl DerefMut for CurvePoints {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.points
     }
 }
 
 /// A point with an adjustable single-control exponential curve
-#[derive(Copy, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct CurvePoint {
     pub x: f32,
     pub y: f32,
-    /// Defines the exponential curve between the current and last point
+    /// Defines the exponential curve between the current and last point. `0`
+    /// is a straight line, positive values start slow and end fast, negative
+    /// values start fast and end slow. See [`curve_shape`].
     pub curve: f32,
 }
 
+impl CurvePoint {
+    /// Shapes a normalized position `t` from `0..=1` along the segment ending
+    /// at this point
+    pub fn shape(&self, t: f32) -> f32 {
+        curve_shape(t, self.curve)
+    }
+}
+
+/// The single-control exponential curve used by [`CurvePoint`]. Maps `t` from
+/// `0..=1` to `0..=1` as `(e^(curve * t) - 1) / (e^curve - 1)`, which is linear
+/// when `curve` is `0`. `curve` is clamped to `-MAX_CURVE..=MAX_CURVE`.
+pub fn curve_shape(t: f32, curve: f32) -> f32 {
+    let t = t.clamp(0f32, 1f32);
+    let curve = curve.clamp(-MAX_CURVE, MAX_CURVE);
+    if curve.abs() < LINEAR_THRESHOLD {
+        t
+    } else {
+        (curve * t).exp_m1() / curve.exp_m1()
+    }
+}
+
 impl From<Vec2> for CurvePoint {
     fn from(v: Vec2) -> Self {
         Self {
@@ -55,3 +261,159 @@ impl From<(f32, f32, f32)> for CurvePoint {
         Self { x, y, curve }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn points() -> CurvePoints {
+        CurvePoints::new(
+            [
+                (0f32, 0f32, 0f32),
+                (1f32, 1f32, 0f32),
+                (2f32, 0.5f32, 4f32),
+                (4f32, 0f32, -4f32),
+            ]
+            .into_iter()
+            .map(CurvePoint::from)
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn linear_shape() {
+        for t in [0f32, 0.25, 0.5, 1.0] {
+            assert_approx_eq!(curve_shape(t, 0f32), t);
+        }
+    }
+
+    #[test]
+    fn shape_endpoints() {
+        for curve in [-MAX_CURVE, -4f32, -0.5, 0.5, 4f32, MAX_CURVE, 100f32] {
+            assert_approx_eq!(curve_shape(0f32, curve), 0f32);
+            assert_approx_eq!(curve_shape(1f32, curve), 1f32);
+        }
+    }
+
+    #[test]
+    fn shape_direction() {
+        assert!(curve_shape(0.5, 4f32) < 0.5);
+        assert!(curve_shape(0.5, -4f32) > 0.5);
+        // Opposite curves mirror each other
+        assert_approx_eq!(curve_shape(0.3, 4f32), 1f32 - curve_shape(0.7, -4f32));
+    }
+
+    #[test]
+    fn gets_segment() {
+        let points = points();
+        assert_eq!(points.segment_at(-1f32), None);
+        assert_eq!(points.segment_at(0f32), Some(0));
+        assert_eq!(points.segment_at(1.5), Some(1));
+        assert_eq!(points.segment_at(2f32), Some(2));
+        assert_eq!(points.segment_at(4f32), None);
+    }
+
+    #[test]
+    fn gets_value() {
+        let points = points();
+        assert_approx_eq!(points.value_at(-1f32), 0f32);
+        assert_approx_eq!(points.value_at(0.5), 0.5);
+        assert_approx_eq!(points.value_at(1f32), 1f32);
+        assert_approx_eq!(points.value_at(1.5), 1f32 - 0.5 * curve_shape(0.5, 4f32));
+        assert_approx_eq!(points.value_at(3f32), 0.5 - 0.5 * curve_shape(0.5, -4f32));
+        assert_approx_eq!(points.value_at(10f32), 0f32);
+        assert_approx_eq!(CurvePoints::new(vec![]).value_at(1f32), 0f32);
+    }
+
+    #[test]
+    fn render_matches_value_at() {
+        let points = points();
+        let sample_rate = 100f32;
+        let start = -0.5;
+        let mut buffer = [0f32; 500];
+        points.render(start, sample_rate, &mut buffer);
+        for (i, sample) in buffer.iter().enumerate() {
+            let x = start + i as f32 / sample_rate;
+            assert_approx_eq!(*sample, points.value_at(x));
+        }
+    }
+
+    #[test]
+    fn render_holds_first_value() {
+        let points = CurvePoints::new(vec![
+            CurvePoint::from((1f32, 0.5)),
+            CurvePoint::from((2f32, 1f32)),
+        ]);
+        let mut buffer = [0f32; 30];
+        points.render(0f32, 10f32, &mut buffer);
+        assert_approx_eq!(buffer[0], 0.5);
+        assert_approx_eq!(buffer[9], 0.5);
+        assert_approx_eq!(buffer[15], 0.75);
+        assert_approx_eq!(buffer[25], 1f32);
+        let mut buffer = [0f32; 3];
+        CurvePoints::new(vec![CurvePoint::from((1f32, 0.5))]).render(0f32, 1f32, &mut buffer);
+        assert_eq!(buffer, [0.5; 3]);
+    }
+
+    #[test]
+    fn insert_shifts_markers() {
+        let mut points = points();
+        points.sustain = Some(2);
+        points.loop_region = Some(LoopRegion { start: 1, end: 2 });
+        points.insert_point(2, CurvePoint::from((1.5, 0.5)));
+        assert_eq!(points.sustain, Some(3));
+        assert_eq!(points.loop_region, Some(LoopRegion { start: 1, end: 3 }));
+        points.insert_point(0, CurvePoint::from((0f32, 0f32)));
+        assert_eq!(points.sustain, Some(4));
+        assert_eq!(points.loop_region, Some(LoopRegion { start: 2, end: 4 }));
+    }
+
+    #[test]
+    fn rejects_invalid_loop_regions() {
+        let mut points = points();
+        points.loop_region = Some(LoopRegion { start: 1, end: 3 });
+        assert_eq!(points.valid_loop_region(), points.loop_region);
+        for (start, end) in [(3, 1), (2, 2), (9, 1), (1, 4)] {
+            points.loop_region = Some(LoopRegion { start, end });
+            assert_eq!(points.valid_loop_region(), None);
+        }
+    }
+
+    #[test]
+    fn loads_points_without_markers() {
+        let points = points();
+        // Points used to be saved as a bare array
+        let json = serde_json::to_string(&points.points).unwrap();
+        let loaded: CurvePoints = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, points);
+        let json = r#"{"points": [{"x": 0.0, "y": 1.0, "curve": 0.0}]}"#;
+        let loaded: CurvePoints = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            loaded,
+            CurvePoints::new(vec![CurvePoint::from((0f32, 1f32))])
+        );
+    }
+
+    #[test]
+    fn round_trips_markers() {
+        let mut points = points();
+        points.sustain = Some(2);
+        points.loop_region = Some(LoopRegion { start: 1, end: 2 });
+        let json = serde_json::to_string(&points).unwrap();
+        assert_eq!(serde_json::from_str::<CurvePoints>(&json).unwrap(), points);
+    }
+
+    #[test]
+    fn remove_shifts_markers() {
+        let mut points = points();
+        points.sustain = Some(3);
+        points.loop_region = Some(LoopRegion { start: 2, end: 3 });
+        points.remove_point(1);
+        assert_eq!(points.sustain, Some(2));
+        assert_eq!(points.loop_region, Some(LoopRegion { start: 1, end: 2 }));
+        points.remove_point(2);
+        assert_eq!(points.sustain, None);
+        assert_eq!(points.loop_region, None);
+    }
+}
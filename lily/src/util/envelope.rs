@@ -0,0 +1,311 @@
+//! Real-time playback of [`CurvePoints`]
+
+use super::CurvePoints;
+
+/// The state of an [`EnvelopePlayer`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvelopeStage {
+    /// Not playing. The output holds at the current position.
+    Idle,
+    /// Moving through the envelope with the gate open
+    Playing,
+    /// Holding at the sustain point until the gate closes
+    Sustaining,
+    /// Moving through the rest of the envelope after the gate closed
+    Releasing,
+}
+
+/// Plays back [`CurvePoints`] one sample at a time. The player does not own
+/// the points and never allocates, so it can run on the audio thread with
+/// points that are edited between blocks.
+///
+/// While the gate is open the envelope loops over its
+/// [`LoopRegion`](super::LoopRegion) and holds at its sustain point, whichever
+/// is reached first. Once the gate closes it plays through to the last point.
+#[derive(Clone, Debug)]
+pub struct EnvelopePlayer {
+    sample_rate: f32,
+    /// The playback position in seconds
+    position: f32,
+    /// The segment containing `position`, cached so we don't have to search
+    /// the points every sample
+    segment: usize,
+    gate: bool,
+    stage: EnvelopeStage,
+    /// Whether the sustain point has been reached since the envelope was
+    /// triggered, as the sustain point may be the first point
+    reached_sustain: bool,
+}
+
+impl EnvelopePlayer {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            position: 0f32,
+            segment: 0,
+            gate: false,
+            stage: EnvelopeStage::Idle,
+            reached_sustain: false,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    pub fn stage(&self) -> EnvelopeStage {
+        self.stage
+    }
+
+    /// The playback position in seconds
+    pub fn position(&self) -> f32 {
+        self.position
+    }
+
+    /// Whether the envelope is still producing a changing output
+    pub fn is_active(&self) -> bool {
+        self.stage != EnvelopeStage::Idle
+    }
+
+    /// Opens the gate. Starts the envelope from the beginning unless the gate
+    /// is already open.
+    pub fn gate_on(&mut self) {
+        if !self.gate {
+            self.retrigger();
+        }
+    }
+
+    /// Closes the gate, releasing the envelope from a sustain or loop
+    pub fn gate_off(&mut self) {
+        self.gate = false;
+        if matches!(
+            self.stage,
+            EnvelopeStage::Playing | EnvelopeStage::Sustaining
+        ) {
+            self.stage = EnvelopeStage::Releasing;
+        }
+    }
+
+    /// Restarts the envelope from the beginning, opening the gate if it was
+    /// closed
+    pub fn retrigger(&mut self) {
+        self.gate = true;
+        self.position = 0f32;
+        self.segment = 0;
+        self.stage = EnvelopeStage::Playing;
+        self.reached_sustain = false;
+    }
+
+    /// Stops playback and rewinds to the beginning
+    pub fn reset(&mut self) {
+        self.gate = false;
+        self.position = 0f32;
+        self.segment = 0;
+        self.stage = EnvelopeStage::Idle;
+        self.reached_sustain = false;
+    }
+
+    /// Gets the current value of `points` and advances by one sample
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self, points: &CurvePoints) -> f32 {
+        if points.is_empty() {
+            return 0f32;
+        }
+
+        // The points may have been edited since the last sample. Past the last
+        // point, the last segment holds its final value.
+        if self.segment + 1 >= points.len() || points[self.segment].x > self.position {
+            self.segment = match points.segment_at(self.position) {
+                Some(segment) => segment,
+                None if self.position >= points[points.len() - 1].x => {
+                    points.len().saturating_sub(2)
+                }
+                None => 0,
+            };
+        }
+
+        let value = match self.stage {
+            EnvelopeStage::Sustaining => match points.valid_sustain() {
+                Some(sustain) => return points[sustain].y,
+                // The sustain point has been removed while holding
+                None => {
+                    self.stage = EnvelopeStage::Playing;
+                    points.value_at(self.position)
+                }
+            },
+            EnvelopeStage::Idle => return points.value_at(self.position),
+            _ => points.segment_value(self.segment, self.position),
+        };
+
+        self.advance(points);
+        value
+    }
+
+    /// Fills `buffer` with consecutive values of `points`
+    pub fn process(&mut self, points: &CurvePoints, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.next(points);
+        }
+    }
+
+    fn advance(&mut self, points: &CurvePoints) {
+        self.position += 1f32 / self.sample_rate;
+
+        if self.gate {
+            if let Some(region) = points.valid_loop_region() {
+                let (start, end) = (points[region.start].x, points[region.end].x);
+                // Only loop once we reach the end from inside the region
+                if self.segment < region.end && self.position >= end {
+                    self.position = start + (self.position - end) % (end - start);
+                    self.segment = region.start;
+                }
+            }
+            if let Some(sustain) = points.valid_sustain() {
+                if !self.reached_sustain && self.position >= points[sustain].x {
+                    self.position = points[sustain].x;
+                    self.segment = sustain;
+                    self.stage = EnvelopeStage::Sustaining;
+                    self.reached_sustain = true;
+                    return;
+                }
+            }
+        }
+
+        while self.segment + 1 < points.len() && points[self.segment + 1].x <= self.position {
+            self.segment += 1;
+        }
+
+        let duration = points.duration();
+        if self.position >= duration {
+            self.position = duration;
+            self.stage = EnvelopeStage::Idle;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{CurvePoint, LoopRegion};
+    use assert_approx_eq::assert_approx_eq;
+
+    const SAMPLE_RATE: f32 = 10f32;
+
+    fn points() -> CurvePoints {
+        CurvePoints::new(
+            [(0f32, 0f32), (1f32, 1f32), (2f32, 0.5f32), (3f32, 0f32)]
+                .into_iter()
+                .map(CurvePoint::from)
+                .collect(),
+        )
+    }
+
+    fn run(player: &mut EnvelopePlayer, points: &CurvePoints, samples: usize) -> Vec<f32> {
+        (0..samples).map(|_| player.next(points)).collect()
+    }
+
+    #[test]
+    fn idle_until_gate() {
+        let points = points();
+        let mut player = EnvelopePlayer::new(SAMPLE_RATE);
+        assert!(run(&mut player, &points, 10).iter().all(|v| *v == 0f32));
+        assert_eq!(player.stage(), EnvelopeStage::Idle);
+    }
+
+    #[test]
+    fn plays_to_end() {
+        let points = points();
+        let mut player = EnvelopePlayer::new(SAMPLE_RATE);
+        player.gate_on();
+        let values = run(&mut player, &points, 40);
+        for (i, value) in values.iter().take(30).enumerate() {
+            assert_approx_eq!(*value, points.value_at(i as f32 / SAMPLE_RATE));
+        }
+        assert_eq!(player.stage(), EnvelopeStage::Idle);
+        assert_approx_eq!(*values.last().unwrap(), 0f32);
+    }
+
+    #[test]
+    fn holds_at_sustain() {
+        let mut points = points();
+        points.sustain = Some(2);
+        let mut player = EnvelopePlayer::new(SAMPLE_RATE);
+        player.gate_on();
+        let values = run(&mut player, &points, 50);
+        assert_eq!(player.stage(), EnvelopeStage::Sustaining);
+        assert_approx_eq!(*values.last().unwrap(), 0.5);
+
+        player.gate_off();
+        assert_eq!(player.stage(), EnvelopeStage::Releasing);
+        let values = run(&mut player, &points, 5);
+        assert_approx_eq!(values[0], 0.5);
+        assert!(values[4] < 0.5);
+    }
+
+    #[test]
+    fn holds_at_first_point_sustain() {
+        let mut points = points();
+        points.sustain = Some(0);
+        let mut player = EnvelopePlayer::new(SAMPLE_RATE);
+        player.gate_on();
+        let values = run(&mut player, &points, 20);
+        assert_eq!(player.stage(), EnvelopeStage::Sustaining);
+        assert!(values.iter().all(|v| *v == 0f32));
+
+        player.gate_off();
+        let values = run(&mut player, &points, 5);
+        assert!(values[4] > 0f32);
+    }
+
+    #[test]
+    fn follows_shortened_points() {
+        let mut points = points();
+        let mut player = EnvelopePlayer::new(SAMPLE_RATE);
+        player.gate_on();
+        run(&mut player, &points, 25);
+        // Remove the last point while playing past the new end
+        points.remove_point(3);
+        assert_approx_eq!(player.next(&points), 0.5);
+        assert_eq!(player.stage(), EnvelopeStage::Idle);
+    }
+
+    #[test]
+    fn loops_while_gated() {
+        let mut points = points();
+        points.loop_region = Some(LoopRegion { start: 1, end: 2 });
+        let mut player = EnvelopePlayer::new(SAMPLE_RATE);
+        player.gate_on();
+        run(&mut player, &points, 100);
+        assert_eq!(player.stage(), EnvelopeStage::Playing);
+        assert!(player.position() >= 1f32 && player.position() < 2f32);
+
+        player.gate_off();
+        run(&mut player, &points, 30);
+        assert_eq!(player.stage(), EnvelopeStage::Idle);
+    }
+
+    #[test]
+    fn release_before_sustain_continues() {
+        let mut points = points();
+        points.sustain = Some(2);
+        let mut player = EnvelopePlayer::new(SAMPLE_RATE);
+        player.gate_on();
+        run(&mut player, &points, 5);
+        player.gate_off();
+        run(&mut player, &points, 30);
+        assert_eq!(player.stage(), EnvelopeStage::Idle);
+    }
+
+    #[test]
+    fn retriggers() {
+        let points = points();
+        let mut player = EnvelopePlayer::new(SAMPLE_RATE);
+        player.gate_on();
+        run(&mut player, &points, 15);
+        // Gate already open
+        player.gate_on();
+        assert!(player.position() > 1f32);
+        player.retrigger();
+        assert_approx_eq!(player.next(&points), 0f32);
+    }
+}
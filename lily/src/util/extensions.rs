@@ -11,6 +11,8 @@ where
     fn width(&self) -> T;
     // Normalize `T`
     fn map(&self, value: T) -> T;
+    /// Denormalize `T`, the inverse of [`map`](Self::map)
+    fn unmap(&self, normalized: T) -> T;
 }
 
 impl<T> RangeExt<T> for RangeInclusive<T>
@@ -24,6 +26,102 @@ where
     fn map(&self, value: T) -> T {
         (value - *self.start()) / self.width()
     }
+
+    fn unmap(&self, normalized: T) -> T {
+        *self.start() + normalized * self.width()
+    }
+}
+
+/// Gains at or below this level in decibels are treated as silence
+pub const MINUS_INFINITY_DB: f32 = -100f32;
+
+/// Converts a level in decibels to a linear gain
+pub fn db_to_gain(db: f32) -> f32 {
+    match db > MINUS_INFINITY_DB {
+        true => 10f32.powf(db / 20f32),
+        false => 0f32,
+    }
+}
+
+/// Converts a linear gain to a level in decibels, no lower than
+/// [`MINUS_INFINITY_DB`]
+pub fn gain_to_db(gain: f32) -> f32 {
+    match gain > 0f32 {
+        true => (20f32 * gain.log10()).max(MINUS_INFINITY_DB),
+        false => MINUS_INFINITY_DB,
+    }
+}
+
+/// How values are spread along a widget, for mapping between a value in a
+/// range and its normalized position from `0..=1`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Taper {
+    #[default]
+    Linear,
+    /// Equal ratios take equal distances, as with frequencies. Both ends of
+    /// the range must be above zero.
+    Logarithmic,
+    /// Raises the linear position to the power of the factor. Factors below
+    /// `1` give more of the widget to the start of the range.
+    Skew(f32),
+    /// A range of gains spread evenly in decibels
+    Decibel,
+    /// Skews both halves of the range away from or towards its centre, for
+    /// ranges like `-1f32..=1f32`. Factors above `1` give more of the widget
+    /// to values near the centre.
+    BipolarSkew(f32),
+}
+
+impl Taper {
+    /// A [`Taper::Skew`] that puts `centre` in the middle of the widget, or
+    /// [`Taper::Linear`] if `centre` isn't strictly within `range`
+    pub fn skew_centred(range: &RangeInclusive<f32>, centre: f32) -> Self {
+        let position = range.map(centre);
+        match position > 0f32 && position < 1f32 {
+            true => Taper::Skew(0.5f32.ln() / position.ln()),
+            false => Taper::Linear,
+        }
+    }
+
+    /// Maps a value in `range` to its normalized position
+    pub fn map(&self, range: &RangeInclusive<f32>, value: f32) -> f32 {
+        match *self {
+            Taper::Linear => range.map(value),
+            Taper::Logarithmic => {
+                // Values outside of the range would have no logarithm
+                let (start, end) = (*range.start(), *range.end());
+                let value = value.clamp(start.min(end), start.max(end));
+                (value / start).ln() / (end / start).ln()
+            }
+            Taper::Skew(factor) => range.map(value).clamp(0f32, 1f32).powf(factor),
+            Taper::Decibel => {
+                let db_range = gain_to_db(*range.start())..=gain_to_db(*range.end());
+                db_range.map(gain_to_db(value))
+            }
+            Taper::BipolarSkew(factor) => {
+                let centred = range.map(value).clamp(0f32, 1f32) * 2f32 - 1f32;
+                (centred.signum() * centred.abs().powf(1f32 / factor) + 1f32) / 2f32
+            }
+        }
+    }
+
+    /// Maps a normalized position to a value in `range`, the inverse of
+    /// [`map`](Self::map)
+    pub fn unmap(&self, range: &RangeInclusive<f32>, normalized: f32) -> f32 {
+        match *self {
+            Taper::Linear => range.unmap(normalized),
+            Taper::Logarithmic => range.start() * (range.end() / range.start()).powf(normalized),
+            Taper::Skew(factor) => range.unmap(normalized.clamp(0f32, 1f32).powf(1f32 / factor)),
+            Taper::Decibel => {
+                let db_range = gain_to_db(*range.start())..=gain_to_db(*range.end());
+                db_to_gain(db_range.unmap(normalized))
+            }
+            Taper::BipolarSkew(factor) => {
+                let centred = normalized.clamp(0f32, 1f32) * 2f32 - 1f32;
+                range.unmap((centred.signum() * centred.abs().powf(factor) + 1f32) / 2f32)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -41,6 +139,67 @@ mod tests {
         assert_approx_eq!((-0.2f32..=0.2).width(), 0.4);
     }
 
+    #[test]
+    fn get_unmapped() {
+        assert_approx_eq!((-1f32..=1f32).unmap(0.25), -0.5);
+        assert_approx_eq!((2.5f32..=-2.5f32).unmap(0f32), 2.5);
+    }
+
+    #[test]
+    fn converts_decibels() {
+        assert_approx_eq!(db_to_gain(0f32), 1f32);
+        assert_approx_eq!(db_to_gain(-6f32), 0.501, 1e-3);
+        assert_approx_eq!(db_to_gain(MINUS_INFINITY_DB), 0f32);
+        assert_approx_eq!(gain_to_db(0.5), -6.021, 1e-3);
+        assert_approx_eq!(gain_to_db(0f32), MINUS_INFINITY_DB);
+    }
+
+    #[test]
+    fn maps_tapers() {
+        let frequency = 20f32..=20_000f32;
+        assert_approx_eq!(Taper::Logarithmic.map(&frequency, 632.456), 0.5, 1e-3);
+        assert_approx_eq!(Taper::Skew(0.5).map(&(0f32..=100f32), 25f32), 0.5);
+        let centred = Taper::skew_centred(&frequency, 1000f32);
+        assert_approx_eq!(centred.map(&frequency, 1000f32), 0.5);
+        let gain = 0f32..=db_to_gain(12f32);
+        assert_approx_eq!(Taper::Decibel.map(&gain, 1f32), 100f32 / 112f32, 1e-3);
+        // Values near the centre take more of the widget
+        let bipolar = Taper::BipolarSkew(2f32);
+        assert_approx_eq!(bipolar.map(&(-1f32..=1f32), 0f32), 0.5);
+        assert!(bipolar.map(&(-1f32..=1f32), 0.25) > 0.625);
+    }
+
+    #[test]
+    fn clamps_tapered_values() {
+        let frequency = 20f32..=20_000f32;
+        assert_approx_eq!(Taper::Logarithmic.map(&frequency, 0f32), 0f32);
+        assert_approx_eq!(Taper::Logarithmic.map(&frequency, -5f32), 0f32);
+        assert_approx_eq!(Taper::Logarithmic.map(&frequency, 40_000f32), 1f32);
+        assert_approx_eq!(Taper::Skew(0.5).map(&(0f32..=100f32), -10f32), 0f32);
+        // A centre outside of the range can't be put in the middle
+        assert_eq!(Taper::skew_centred(&frequency, 10f32), Taper::Linear);
+        assert_eq!(Taper::skew_centred(&frequency, 20_000f32), Taper::Linear);
+    }
+
+    #[test]
+    fn round_trips_tapers() {
+        let tests = [
+            (Taper::Linear, -1f32..=1f32, [-1f32, -0.3, 0.5, 1f32]),
+            (Taper::Logarithmic, 20f32..=20_000f32, [20f32, 100f32, 4000f32, 20_000f32]),
+            (Taper::Skew(0.3), 0f32..=10f32, [0f32, 0.1, 5f32, 10f32]),
+            (Taper::Decibel, 0f32..=2f32, [0.001, 0.5, 1f32, 2f32]),
+            (Taper::BipolarSkew(3f32), -5f32..=5f32, [-5f32, -0.2, 1f32, 5f32]),
+            (Taper::BipolarSkew(0.5), -1f32..=1f32, [-1f32, -0.5, 0f32, 0.8]),
+        ];
+        for (taper, range, values) in tests {
+            for value in values {
+                let normalized = taper.map(&range, value);
+                assert!((0f32..=1f32).contains(&normalized), "{:?} {}", taper, value);
+                assert_approx_eq!(taper.unmap(&range, normalized), value, 1e-2);
+            }
+        }
+    }
+
     #[test]
     fn get_mapped() {
         let tests = [
@@ -0,0 +1,222 @@
+//! Undo and redo for widget edits
+
+use std::collections::VecDeque;
+use vizia::prelude::{Code, Modifiers};
+
+/// The number of undo steps a [`History`] keeps by default
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// An action on a [`History`] requested through the keyboard
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryAction {
+    Undo,
+    Redo,
+}
+
+impl HistoryAction {
+    /// Gets the action bound to a key press. `ctrl+z` undoes, while
+    /// `ctrl+shift+z` and `ctrl+y` redo. `cmd` works in place of `ctrl`.
+    pub fn from_key(code: Code, modifiers: Modifiers) -> Option<Self> {
+        if !(modifiers.contains(Modifiers::CTRL) || modifiers.contains(Modifiers::LOGO)) {
+            return None;
+        }
+        match code {
+            Code::KeyZ if modifiers.contains(Modifiers::SHIFT) => Some(Self::Redo),
+            Code::KeyZ => Some(Self::Undo),
+            Code::KeyY => Some(Self::Redo),
+            _ => None,
+        }
+    }
+}
+
+/// A bounded stack of snapshots of some state `T`, such as
+/// [`CurvePoints`](super::CurvePoints). Record the state before every edit
+/// with [`History::record`], and wrap continuous edits like drags in
+/// [`History::begin_gesture`] and [`History::end_gesture`] so they are undone
+/// in a single step. The state is only taken once per gesture, when it
+/// begins.
+#[derive(Clone, Debug)]
+pub struct History<T> {
+    undo_stack: VecDeque<T>,
+    redo_stack: Vec<T>,
+    /// The maximum number of undo steps to keep
+    limit: usize,
+    /// Whether we are in the middle of a gesture
+    in_gesture: bool,
+    /// The state at the start of the current gesture, until its first edit
+    gesture_start: Option<T>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_LIMIT)
+    }
+}
+
+impl<T> History<T> {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            limit,
+            in_gesture: false,
+            gesture_start: None,
+        }
+    }
+
+    /// Records `before`, the state prior to an edit, as an undo step. Any redo
+    /// steps are discarded.
+    pub fn record(&mut self, before: T) {
+        self.record_with(|| before);
+    }
+
+    /// Records the state prior to an edit like [`History::record`], only
+    /// calling `before` to get it when needed. During a gesture, the first
+    /// edit records the state from when the gesture began, and `before` is
+    /// never called.
+    pub fn record_with<F>(&mut self, before: F)
+    where
+        F: FnOnce() -> T,
+    {
+        let before = match self.in_gesture {
+            true => match self.gesture_start.take() {
+                Some(start) => start,
+                None => return,
+            },
+            false => before(),
+        };
+        self.redo_stack.clear();
+        self.undo_stack.push_back(before);
+        if self.undo_stack.len() > self.limit {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Starts coalescing edits into a single undo step, given the state
+    /// `before` the gesture. Gestures without edits don't add a step.
+    pub fn begin_gesture(&mut self, before: T) {
+        self.in_gesture = true;
+        self.gesture_start = Some(before);
+    }
+
+    /// Stops coalescing edits
+    pub fn end_gesture(&mut self) {
+        self.in_gesture = false;
+        self.gesture_start = None;
+    }
+
+    /// Steps back, given the `current` state. Returns the state to restore, or
+    /// `None` if there is nothing to undo.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let previous = self.undo_stack.pop_back()?;
+        self.redo_stack.push(current);
+        Some(previous)
+    }
+
+    /// Steps forward again after an undo, given the `current` state. Returns
+    /// the state to restore, or `None` if there is nothing to redo.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let next = self.redo_stack.pop()?;
+        self.undo_stack.push_back(current);
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Forgets all undo and redo steps
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo() {
+        let mut history = History::default();
+        history.record(0);
+        history.record(1);
+        // The current state is 2
+        assert_eq!(history.undo(2), Some(1));
+        assert_eq!(history.undo(1), Some(0));
+        assert_eq!(history.undo(0), None);
+        assert_eq!(history.redo(0), Some(1));
+        assert_eq!(history.redo(1), Some(2));
+        assert_eq!(history.redo(2), None);
+    }
+
+    #[test]
+    fn record_clears_redo() {
+        let mut history = History::default();
+        history.record(0);
+        assert_eq!(history.undo(1), Some(0));
+        assert!(history.can_redo());
+        history.record(0);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn gesture_is_one_step() {
+        let mut history = History::default();
+        history.begin_gesture(0);
+        for state in 0..10 {
+            history.record(state);
+        }
+        history.end_gesture();
+        history.record(10);
+        assert_eq!(history.undo(11), Some(10));
+        assert_eq!(history.undo(10), Some(0));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn gesture_takes_state_once() {
+        let mut history = History::default();
+        history.begin_gesture(0);
+        history.record_with(|| 1);
+        history.record_with(|| unreachable!());
+        history.end_gesture();
+        assert_eq!(history.undo(2), Some(0));
+        // A gesture without edits adds nothing
+        history.begin_gesture(0);
+        history.end_gesture();
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn limits_steps() {
+        let mut history = History::new(2);
+        for state in 0..5 {
+            history.record(state);
+        }
+        assert_eq!(history.undo(5), Some(4));
+        assert_eq!(history.undo(4), Some(3));
+        assert_eq!(history.undo(3), None);
+    }
+
+    #[test]
+    fn keyboard_shortcuts() {
+        assert_eq!(
+            HistoryAction::from_key(Code::KeyZ, Modifiers::CTRL),
+            Some(HistoryAction::Undo)
+        );
+        assert_eq!(
+            HistoryAction::from_key(Code::KeyZ, Modifiers::LOGO | Modifiers::SHIFT),
+            Some(HistoryAction::Redo)
+        );
+        assert_eq!(
+            HistoryAction::from_key(Code::KeyY, Modifiers::CTRL),
+            Some(HistoryAction::Redo)
+        );
+        assert_eq!(HistoryAction::from_key(Code::KeyZ, Modifiers::empty()), None);
+    }
+}
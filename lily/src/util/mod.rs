@@ -1,4 +1,13 @@
 mod curve_point;
+mod envelope;
 mod extensions;
+mod history;
+mod parameter;
+mod steps;
+mod units;
+mod viewport;
 mod vizia_extensions;
-pub use {curve_point::*, extensions::*, vizia_extensions::*};
+pub use {
+    curve_point::*, envelope::*, extensions::*, history::*, parameter::*, steps::*, units::*,
+    viewport::*, vizia_extensions::*,
+};
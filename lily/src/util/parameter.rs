@@ -0,0 +1,260 @@
+//! A definition of a value that drives its mapping, display and text entry
+
+use crate::util::{parse_value_in, Taper};
+use std::{fmt, ops::RangeInclusive, rc::Rc};
+
+/// Describes a parameter, such as a cutoff frequency or a gain, so that every
+/// widget showing it maps, displays and parses it the same way.
+///
+/// ```ignore
+/// let cutoff = Parameter::new(20f32..=20_000f32)
+///     .with_taper(Taper::Logarithmic)
+///     .with_default(1000f32)
+///     .with_unit("Hz");
+/// ```
+#[allow(clippy::type_complexity)]
+#[derive(Clone)]
+pub struct Parameter {
+    /// The range of the plain value
+    pub range: RangeInclusive<f32>,
+    /// How values are spread along a widget
+    pub taper: Taper,
+    /// The distance between allowed values, if any
+    pub step: Option<f32>,
+    /// The value to reset to, if any
+    pub default: Option<f32>,
+    /// The unit shown after the value, like `"Hz"`
+    pub unit: &'static str,
+    value_to_string: Option<Rc<dyn Fn(f32) -> String>>,
+    string_to_value: Option<Rc<dyn Fn(&str) -> Option<f32>>>,
+}
+
+impl Parameter {
+    /// Create a new linear `Parameter` over `range`
+    pub fn new(range: RangeInclusive<f32>) -> Self {
+        Self {
+            range,
+            taper: Taper::Linear,
+            step: None,
+            default: None,
+            unit: "",
+            value_to_string: None,
+            string_to_value: None,
+        }
+    }
+
+    /// Sets how values are spread along a widget
+    ///
+    /// # Panics
+    ///
+    /// Panics if `taper` is [`Taper::Logarithmic`] and the range isn't above
+    /// zero, as there would be no logarithm to map values with, or if `taper`
+    /// is a skew with a factor that isn't above zero
+    pub fn with_taper(mut self, taper: Taper) -> Self {
+        let (start, end) = (*self.range.start(), *self.range.end());
+        match taper {
+            Taper::Logarithmic => assert!(
+                start > 0f32 && end > 0f32,
+                "a logarithmic taper needs a range above zero, not {:?}",
+                self.range
+            ),
+            Taper::Skew(factor) | Taper::BipolarSkew(factor) => assert!(
+                factor > 0f32,
+                "a skew needs a factor above zero, not {}",
+                factor
+            ),
+            Taper::Linear | Taper::Decibel => (),
+        }
+        self.taper = taper;
+        self
+    }
+
+    /// Raises the linear position in the range to the power of `skew`, a
+    /// shorthand for [`Taper::Skew`]
+    pub fn with_skew(self, skew: f32) -> Self {
+        self.with_taper(Taper::Skew(skew))
+    }
+
+    /// Only allow values `step` apart from the start of the range
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    /// Sets the value to reset to
+    pub fn with_default(mut self, default: f32) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    /// Sets the unit shown after the value
+    pub fn with_unit(mut self, unit: &'static str) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Formats values with `f` rather than with two decimals and the unit
+    pub fn with_value_to_string<F>(mut self, f: F) -> Self
+    where
+        F: 'static + Fn(f32) -> String,
+    {
+        self.value_to_string = Some(Rc::new(f));
+        self
+    }
+
+    /// Parses typed text with `f` rather than with
+    /// [`parse_value_in`](crate::util::parse_value_in)
+    pub fn with_string_to_value<F>(mut self, f: F) -> Self
+    where
+        F: 'static + Fn(&str) -> Option<f32>,
+    {
+        self.string_to_value = Some(Rc::new(f));
+        self
+    }
+
+    /// Maps a plain value to its normalized position from `0..=1`
+    pub fn normalize(&self, value: f32) -> f32 {
+        self.taper.map(&self.range, value).clamp(0f32, 1f32)
+    }
+
+    /// Maps a normalized position from `0..=1` to a plain value, snapped to
+    /// the step size
+    pub fn denormalize(&self, normalized: f32) -> f32 {
+        self.snap(self.taper.unmap(&self.range, normalized.clamp(0f32, 1f32)))
+    }
+
+    /// Snaps a plain value to the step size and keeps it within the range
+    pub fn snap(&self, value: f32) -> f32 {
+        let value = match self.step {
+            Some(step) if step > 0f32 => {
+                self.range.start() + ((value - self.range.start()) / step).round() * step
+            }
+            _ => value,
+        };
+        let (start, end) = (*self.range.start(), *self.range.end());
+        value.clamp(start.min(end), start.max(end))
+    }
+
+    /// Formats a plain value for display
+    pub fn format(&self, value: f32) -> String {
+        match (&self.value_to_string, self.unit) {
+            (Some(f), _) => (f)(value),
+            (None, "") => format!("{:.2}", value),
+            (None, unit) => format!("{:.2} {}", value, unit),
+        }
+    }
+
+    /// Parses typed text into a plain value within the range, converting
+    /// from other units of the same quantity, or `None` if it isn't a value
+    /// in a compatible unit
+    pub fn parse(&self, text: &str) -> Option<f32> {
+        let value = match &self.string_to_value {
+            Some(f) => (f)(text),
+            None => parse_value_in(text, self.unit),
+        }?;
+        Some(self.snap(value))
+    }
+}
+
+impl From<RangeInclusive<f32>> for Parameter {
+    fn from(range: RangeInclusive<f32>) -> Self {
+        Parameter::new(range)
+    }
+}
+
+impl fmt::Debug for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Parameter")
+            .field("range", &self.range)
+            .field("taper", &self.taper)
+            .field("step", &self.step)
+            .field("default", &self.default)
+            .field("unit", &self.unit)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::parse_value;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn normalizes_skewed_values() {
+        let param = Parameter::new(0f32..=100f32).with_skew(0.5);
+        assert_approx_eq!(param.normalize(25f32), 0.5);
+        assert_approx_eq!(param.denormalize(0.5), 25f32);
+        assert_approx_eq!(param.normalize(200f32), 1f32);
+        for value in [0f32, 10f32, 60f32, 100f32] {
+            assert_approx_eq!(param.denormalize(param.normalize(value)), value, 1e-3);
+        }
+    }
+
+    #[test]
+    fn normalizes_tapered_values() {
+        let param = Parameter::new(20f32..=20_000f32).with_taper(Taper::Logarithmic);
+        assert_approx_eq!(param.normalize(632.456), 0.5, 1e-3);
+        assert_approx_eq!(param.denormalize(0.5), 632.456, 1e-2);
+        assert_approx_eq!(param.normalize(10f32), 0f32);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_logarithmic_ranges_through_zero() {
+        Parameter::new(0f32..=1f32).with_taper(Taper::Logarithmic);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_zero_skew() {
+        Parameter::new(0f32..=1f32).with_skew(0f32);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_zero_bipolar_skew() {
+        Parameter::new(-1f32..=1f32).with_taper(Taper::BipolarSkew(0f32));
+    }
+
+    #[test]
+    fn snaps_to_steps() {
+        let param = Parameter::new(-12f32..=12f32).with_step(1f32);
+        assert_approx_eq!(param.denormalize(0.52), 0f32);
+        assert_approx_eq!(param.snap(3.6), 4f32);
+        assert_approx_eq!(param.snap(20f32), 12f32);
+    }
+
+    #[test]
+    fn formats_values() {
+        assert_eq!(Parameter::new(0f32..=1f32).format(0.5), "0.50");
+        let param = Parameter::new(20f32..=20_000f32).with_unit("Hz");
+        assert_eq!(param.format(440f32), "440.00 Hz");
+        let param = param.with_value_to_string(|value| format!("{:.0}", value));
+        assert_eq!(param.format(440f32), "440");
+    }
+
+    #[test]
+    fn parses_values() {
+        let param = Parameter::new(-24f32..=24f32).with_unit("st");
+        assert_approx_eq!(param.parse("12 st").unwrap(), 12f32);
+        assert_approx_eq!(param.parse("-7").unwrap(), -7f32);
+        assert_approx_eq!(param.parse("100").unwrap(), 24f32);
+        assert_eq!(param.parse("st"), None);
+        let param = Parameter::new(0f32..=1f32).with_string_to_value(|text| {
+            parse_value(text.trim_end_matches('%')).map(|value| value / 100f32)
+        });
+        assert_approx_eq!(param.parse("50%").unwrap(), 0.5);
+    }
+
+    #[test]
+    fn parses_other_units() {
+        let param = Parameter::new(0f32..=10f32).with_unit("s");
+        assert_approx_eq!(param.parse("250 ms").unwrap(), 0.25);
+        assert_approx_eq!(param.parse("2 s").unwrap(), 2f32);
+        assert_eq!(param.parse("440 Hz"), None);
+        let param = Parameter::new(20f32..=20_000f32).with_unit("Hz");
+        assert_approx_eq!(param.parse("1.5 kHz").unwrap(), 1500f32);
+        assert_eq!(param.parse("-6 dB"), None);
+    }
+}
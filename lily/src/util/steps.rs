@@ -0,0 +1,138 @@
+//! The options of discrete widgets
+
+/// The options a discrete widget can be set to. Sliders are divided into one
+/// equally sized cell per option, with each option sitting at the centre of
+/// its cell. Knobs instead spread their options as detents from one end of
+/// their arc to the other.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Steps {
+    /// A number of unlabelled options
+    Count(usize),
+    /// Options with a label each, such as waveform names
+    Labels(Vec<String>),
+}
+
+impl Steps {
+    /// The number of options
+    pub fn len(&self) -> usize {
+        match self {
+            Steps::Count(count) => *count,
+            Steps::Labels(labels) => labels.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The label of the option at `index`, if the options are labelled
+    pub fn label(&self, index: usize) -> Option<&str> {
+        match self {
+            Steps::Count(_) => None,
+            Steps::Labels(labels) => labels.get(index).map(String::as_str),
+        }
+    }
+
+    /// Gets the index of the option at a normalized `position` along the
+    /// widget. Positions outside of `0..=1` pick the first or last option.
+    pub fn index_at(&self, position: f32) -> usize {
+        let len = self.len();
+        ((position * len as f32).floor().max(0f32) as usize).min(len.saturating_sub(1))
+    }
+
+    /// Gets the normalized position of the option at `index` along the widget
+    pub fn position(&self, index: usize) -> f32 {
+        match self.len() {
+            0 => 0f32,
+            len => (index.min(len - 1) as f32 + 0.5) / len as f32,
+        }
+    }
+
+    /// Gets the normalized position of the detent of the option at `index`,
+    /// where the first option is at `0` and the last at `1`
+    pub fn detent(&self, index: usize) -> f32 {
+        match self.len() {
+            0 | 1 => 0f32,
+            len => index.min(len - 1) as f32 / (len - 1) as f32,
+        }
+    }
+
+    /// Gets the index of the detent closest to a normalized `position`
+    pub fn nearest_detent(&self, position: f32) -> usize {
+        let last = self.len().saturating_sub(1);
+        (position.clamp(0f32, 1f32) * last as f32).round() as usize
+    }
+}
+
+impl From<usize> for Steps {
+    fn from(count: usize) -> Self {
+        Steps::Count(count)
+    }
+}
+
+impl From<Vec<String>> for Steps {
+    fn from(labels: Vec<String>) -> Self {
+        Steps::Labels(labels)
+    }
+}
+
+impl From<&[&str]> for Steps {
+    fn from(labels: &[&str]) -> Self {
+        Steps::Labels(labels.iter().map(|label| label.to_string()).collect())
+    }
+}
+
+impl<const N: usize> From<[&str; N]> for Steps {
+    fn from(labels: [&str; N]) -> Self {
+        Steps::from(&labels[..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn gets_index_at_position() {
+        let steps = Steps::Count(4);
+        assert_eq!(steps.index_at(0f32), 0);
+        assert_eq!(steps.index_at(0.3), 1);
+        assert_eq!(steps.index_at(0.74), 2);
+        assert_eq!(steps.index_at(1f32), 3);
+        assert_eq!(steps.index_at(-0.5), 0);
+        assert_eq!(steps.index_at(1.5), 3);
+    }
+
+    #[test]
+    fn gets_position_of_index() {
+        let steps = Steps::from(["sine", "square", "saw"]);
+        assert_approx_eq!(steps.position(0), 1f32 / 6f32);
+        assert_approx_eq!(steps.position(1), 0.5);
+        assert_approx_eq!(steps.position(2), 5f32 / 6f32);
+        for index in 0..steps.len() {
+            assert_eq!(steps.index_at(steps.position(index)), index);
+        }
+    }
+
+    #[test]
+    fn gets_detents() {
+        let steps = Steps::Count(5);
+        assert_approx_eq!(steps.detent(0), 0f32);
+        assert_approx_eq!(steps.detent(1), 0.25);
+        assert_approx_eq!(steps.detent(4), 1f32);
+        assert_eq!(steps.nearest_detent(0.3), 1);
+        assert_eq!(steps.nearest_detent(0.9), 4);
+        assert_eq!(steps.nearest_detent(-1f32), 0);
+        assert_approx_eq!(Steps::Count(1).detent(0), 0f32);
+        assert_eq!(Steps::Count(1).nearest_detent(0.8), 0);
+    }
+
+    #[test]
+    fn gets_labels() {
+        let steps = Steps::from(["sine", "square"]);
+        assert_eq!(steps.label(1), Some("square"));
+        assert_eq!(steps.label(2), None);
+        assert_eq!(Steps::Count(2).label(0), None);
+    }
+}
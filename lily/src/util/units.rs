@@ -0,0 +1,163 @@
+//! Parsing of values typed in by the user
+
+/// What a unit measures. Values can only be converted between units of the
+/// same quantity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Quantity {
+    Gain,
+    Frequency,
+    Time,
+    Ratio,
+}
+
+/// Units that may follow a typed value, with what they measure and their
+/// size in the base unit of that quantity. Longer units come first so that
+/// `"ms"` isn't read as `"s"`.
+const UNITS: [(&str, Quantity, f32); 5] = [
+    ("db", Quantity::Gain, 1f32),
+    ("hz", Quantity::Frequency, 1f32),
+    ("ms", Quantity::Time, 0.001),
+    ("s", Quantity::Time, 1f32),
+    ("%", Quantity::Ratio, 1f32),
+];
+
+/// Parses a typed value such as `"440"`, `"-6 dB"` or `"1.5kHz"`, ignoring
+/// case and whitespace. A `k` before the unit multiplies the value by a
+/// thousand. Units are only stripped, so `"20ms"` parses as `20`; use
+/// [`parse_value_in`] to convert between units. Returns `None` if the text
+/// isn't a number.
+pub fn parse_value(text: &str) -> Option<f32> {
+    split_value(text).map(|(value, _)| value)
+}
+
+/// Parses a typed value like [`parse_value`], converting it to `unit` when it
+/// was typed in another unit of the same quantity, so `"20ms"` parses as
+/// `0.02` seconds and `"1.5kHz"` as `1500` hertz. Returns `None` if the typed
+/// unit measures something else, like `"-6dB"` for a frequency. Units this
+/// doesn't know about, like `"st"`, are stripped when typed exactly.
+pub fn parse_value_in(text: &str, unit: &str) -> Option<f32> {
+    let (quantity, scale) = match find_unit(unit) {
+        Some(found) => found,
+        None => {
+            let text = text.trim();
+            let text = match text.len() >= unit.len()
+                && text.is_char_boundary(text.len() - unit.len())
+                && text[text.len() - unit.len()..].eq_ignore_ascii_case(unit)
+            {
+                true => &text[..text.len() - unit.len()],
+                false => text,
+            };
+            return parse_value(text);
+        }
+    };
+    match split_value(text)? {
+        (value, None) => Some(value),
+        (value, Some((typed, typed_scale))) if typed == quantity => {
+            Some(value * typed_scale / scale)
+        }
+        (_, Some(_)) => None,
+    }
+}
+
+/// Looks up what `unit` measures and its size, allowing a `k` prefix like in
+/// `"kHz"`
+fn find_unit(unit: &str) -> Option<(Quantity, f32)> {
+    let unit = unit.trim().to_lowercase();
+    let find = |unit: &str| {
+        UNITS
+            .iter()
+            .find(|(name, _, _)| *name == unit)
+            .map(|(_, quantity, scale)| (*quantity, *scale))
+    };
+    find(&unit).or_else(|| {
+        let (quantity, scale) = find(unit.strip_prefix('k')?)?;
+        Some((quantity, scale * 1000f32))
+    })
+}
+
+/// Splits typed text into its value, including any `k` multiplier, and the
+/// unit after it if there is one
+fn split_value(text: &str) -> Option<(f32, Option<(Quantity, f32)>)> {
+    let text = text.trim().to_lowercase();
+    let mut number = text.as_str();
+    let mut unit = None;
+    let found = UNITS.iter().find(|(name, _, _)| number.ends_with(name));
+    if let Some((name, quantity, scale)) = found {
+        number = number[..number.len() - name.len()].trim_end();
+        unit = Some((*quantity, *scale));
+    }
+    let mut multiplier = 1f32;
+    if let Some(stripped) = number.strip_suffix('k') {
+        number = stripped.trim_end();
+        multiplier = 1000f32;
+    }
+    let value = number.parse::<f32>().ok()?;
+    match value.is_nan() {
+        true => None,
+        false => Some((value * multiplier, unit)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn parses_plain_values() {
+        assert_approx_eq!(parse_value("440").unwrap(), 440f32);
+        assert_approx_eq!(parse_value("  -0.5 ").unwrap(), -0.5);
+        assert_approx_eq!(parse_value("+3").unwrap(), 3f32);
+    }
+
+    #[test]
+    fn parses_units() {
+        assert_approx_eq!(parse_value("-6dB").unwrap(), -6f32);
+        assert_approx_eq!(parse_value("-6 db").unwrap(), -6f32);
+        assert_approx_eq!(parse_value("440 Hz").unwrap(), 440f32);
+        assert_approx_eq!(parse_value("20ms").unwrap(), 20f32);
+        assert_approx_eq!(parse_value("1.5 s").unwrap(), 1.5);
+        assert_approx_eq!(parse_value("50%").unwrap(), 50f32);
+    }
+
+    #[test]
+    fn parses_thousands() {
+        assert_approx_eq!(parse_value("1.5k").unwrap(), 1500f32);
+        assert_approx_eq!(parse_value("2kHz").unwrap(), 2000f32);
+        assert_approx_eq!(parse_value("2 k Hz").unwrap(), 2000f32);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert_eq!(parse_value(""), None);
+        assert_eq!(parse_value("dB"), None);
+        assert_eq!(parse_value("k"), None);
+        assert_eq!(parse_value("loud"), None);
+        assert_eq!(parse_value("NaN"), None);
+    }
+
+    #[test]
+    fn converts_units() {
+        assert_approx_eq!(parse_value_in("20ms", "s").unwrap(), 0.02);
+        assert_approx_eq!(parse_value_in("1.5 s", "ms").unwrap(), 1500f32, 1e-3);
+        assert_approx_eq!(parse_value_in("20", "ms").unwrap(), 20f32);
+        assert_approx_eq!(parse_value_in("2kHz", "Hz").unwrap(), 2000f32);
+        assert_approx_eq!(parse_value_in("500 Hz", "kHz").unwrap(), 0.5);
+        assert_approx_eq!(parse_value_in("1.5 khz", "kHz").unwrap(), 1.5);
+        assert_approx_eq!(parse_value_in("-6 dB", "dB").unwrap(), -6f32);
+    }
+
+    #[test]
+    fn rejects_mismatched_units() {
+        assert_eq!(parse_value_in("-6 dB", "Hz"), None);
+        assert_eq!(parse_value_in("20ms", "Hz"), None);
+        assert_eq!(parse_value_in("440 Hz", "s"), None);
+        assert_eq!(parse_value_in("50%", "dB"), None);
+    }
+
+    #[test]
+    fn strips_unknown_units() {
+        assert_approx_eq!(parse_value_in("12 st", "st").unwrap(), 12f32);
+        assert_approx_eq!(parse_value_in("-6 dB", "").unwrap(), -6f32);
+    }
+}
@@ -0,0 +1,65 @@
+//! The zoomed area of a graph
+
+use std::ops::RangeInclusive;
+
+use vizia::prelude::*;
+
+use super::RangeExt;
+
+/// The section of a graph that is zoomed into. Both axes are normalized, so
+/// each range can be any set of numbers between 0 and 1 inclusive where the
+/// start is less than the end. The `y` axis goes from the bottom up.
+#[derive(Clone, Debug, PartialEq, Lens, Data)]
+pub struct Viewport {
+    /// The visible section of the horizontal axis
+    pub x: RangeInclusive<f32>,
+    /// The visible section of the vertical axis
+    pub y: RangeInclusive<f32>,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            x: 0f32..=1f32,
+            y: 0f32..=1f32,
+        }
+    }
+}
+
+impl Viewport {
+    pub fn new(x: RangeInclusive<f32>, y: RangeInclusive<f32>) -> Self {
+        Self { x, y }
+    }
+
+    /// Maps a normalized point to its position within the viewport, where
+    /// `(0,0)` is the bottom left corner and `(1,1)` the top right
+    pub fn map(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.x.map(x), self.y.map(y))
+    }
+
+    /// Gets the normalized point at a position within the viewport. The
+    /// inverse of [`Viewport::map`].
+    pub fn unmap(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.x.start() + x * self.x.width(),
+            self.y.start() + y * self.y.width(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn maps_points() {
+        let viewport = Viewport::new(0.2..=0.6, 0f32..=0.5);
+        let (x, y) = viewport.map(0.3, 0.25);
+        assert_approx_eq!(x, 0.25);
+        assert_approx_eq!(y, 0.5);
+        let (x, y) = viewport.unmap(x, y);
+        assert_approx_eq!(x, 0.3);
+        assert_approx_eq!(y, 0.25);
+    }
+}
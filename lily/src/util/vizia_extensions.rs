@@ -2,6 +2,7 @@
 
 use glam::Vec2;
 use vizia::cache::BoundingBox;
+use vizia::prelude::{Context, Entity, EventContext, View};
 use vizia::*;
 
 pub trait BoundingBoxExt {
@@ -56,6 +57,57 @@ impl BoundingBoxExt for BoundingBox {
     }
 }
 
+/// Modifies the view of type `V` built for `entity`, if there is one. This is
+/// how handle modifiers reach views after they have been built.
+pub(crate) fn modify_view<V, F>(cx: &mut Context, entity: Entity, f: F)
+where
+    V: View,
+    F: FnOnce(&mut V),
+{
+    if let Some(view) = cx.views.get_mut(&entity) {
+        if let Some(view) = view.downcast_mut::<V>() {
+            (f)(view);
+        }
+    }
+}
+
+/// A callback for the start or end of a drag. Hosts group the changes made
+/// between the two into a single automation gesture.
+pub(crate) type DragCallback = Option<Box<dyn Fn(&mut EventContext)>>;
+
+/// Captures the cursor for a drag, marks it as `active` and calls
+/// `on_drag_start`
+pub(crate) fn start_drag(cx: &mut EventContext, active: &mut bool, on_drag_start: &DragCallback) {
+    cx.capture();
+    *active = true;
+    if let Some(callback) = on_drag_start {
+        (callback)(cx);
+    }
+}
+
+/// Releases the cursor and calls `on_drag_end`, if `active`
+pub(crate) fn end_drag(cx: &mut EventContext, active: &mut bool, on_drag_end: &DragCallback) {
+    if !*active {
+        return;
+    }
+    cx.release();
+    *active = false;
+    if let Some(callback) = on_drag_end {
+        (callback)(cx);
+    }
+}
+
+/// Calls `callback` with the `default` value, if there are both
+pub(crate) fn reset_to_default(
+    cx: &mut EventContext,
+    default: Option<f32>,
+    callback: Option<&dyn Fn(&mut EventContext, f32)>,
+) {
+    if let (Some(default), Some(callback)) = (default, callback) {
+        (callback)(cx, default);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,66 @@
+//! Inline text entry for typing exact values into a widget
+
+use vizia::prelude::*;
+
+/// The state of a widget's text entry, built within the widget so that its
+/// events reach the widget
+#[derive(Lens)]
+pub(crate) struct EntryData {
+    text: String,
+    editing: bool,
+}
+
+pub(crate) enum EntryEvent {
+    /// Show the text entry, starting with `text`
+    Open(String),
+    /// Sent with the typed text when the user presses enter
+    Submit(String),
+    Close,
+}
+
+impl Model for EntryData {
+    fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+        event.map(|ev: &EntryEvent, _| match ev {
+            EntryEvent::Open(text) => {
+                self.text = text.clone();
+                self.editing = true;
+            }
+            EntryEvent::Close => self.editing = false,
+            EntryEvent::Submit(_) => (),
+        });
+        // Cancel the entry, keeping the current value
+        event.map(|ev: &WindowEvent, _| {
+            if let WindowEvent::KeyDown(Code::Escape, _) = ev {
+                self.editing = false;
+            }
+        });
+    }
+}
+
+/// Builds a text entry covering the current widget, hidden until an
+/// [`EntryEvent::Open`] is emitted. The widget handles
+/// [`EntryEvent::Submit`] to parse the typed text.
+pub(crate) fn build_entry(cx: &mut Context) {
+    EntryData {
+        text: String::new(),
+        editing: false,
+    }
+    .build(cx);
+    Binding::new(cx, EntryData::editing, |cx, editing| {
+        if editing.get(cx) {
+            Textbox::new(cx, EntryData::text)
+                .class("entry")
+                .position_type(PositionType::SelfDirected)
+                .width(Stretch(1f32))
+                .height(Stretch(1f32))
+                .on_build(|cx| {
+                    cx.emit(TextEvent::StartEdit);
+                    cx.emit(TextEvent::SelectAll);
+                })
+                .on_submit(|cx, text| {
+                    cx.emit(EntryEvent::Submit(text));
+                    cx.emit(EntryEvent::Close);
+                });
+        }
+    });
+}
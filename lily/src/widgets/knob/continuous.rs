@@ -0,0 +1,346 @@
+use crate::util::{end_drag, modify_view, reset_to_default, start_drag, DragCallback, Parameter};
+use glam::Vec2;
+use lily_derive::Handle;
+use std::{collections::HashMap, f32::consts::PI, ops::RangeInclusive};
+use vizia::prelude::*;
+use vizia::vg::{LineCap, Paint, Path, Solidity};
+
+/// The angle of the minimum value, measured clockwise from the right
+pub(crate) const START_ANGLE: f32 = 0.75 * PI;
+/// The angle between the minimum and maximum values
+pub(crate) const SWEEP_ANGLE: f32 = 1.5 * PI;
+/// The distance in pixels to drag to sweep the whole range
+pub(crate) const DRAG_DISTANCE: f32 = 200f32;
+/// How much slower the value changes while holding shift
+const FINE_SCALAR: f32 = 0.1;
+/// The thickness in pixels of the arcs
+pub(crate) const ARC_WIDTH: f32 = 4f32;
+
+/// A rotary knob. Drag up or right to increase the value and down or left to
+/// decrease it, holding shift for finer control. Double-click or ctrl/cmd-click
+/// to reset it to its [`default_value`](KnobModifiers::default_value).
+///
+/// Ranges that span zero, like `-1f32..=1f32`, are bipolar and fill from the
+/// centre of the knob.
+#[derive(Handle)]
+pub struct Knob<L, M>
+where
+    L: Lens<Target = f32>,
+    M: Lens<Target = f32>,
+{
+    value: L,
+    /// An optional [`Lens`] of a modulation amount, drawn as an outer ring.
+    /// The amount is normalized, so `0.5` modulates the value up by half of
+    /// the range.
+    modulation: Option<M>,
+    param: Parameter,
+    hover: bool,
+    active: bool,
+    /// The last cursor position while dragging
+    drag_position: Vec2,
+    /// The normalized value while dragging, so that steps too small to change
+    /// the value are not lost
+    drag_value: f32,
+    classes: HashMap<&'static str, Entity>,
+    #[callback(f32)]
+    on_changing: Option<Box<dyn Fn(&mut EventContext, f32)>>,
+    /// Called with the default value when the knob is reset. Falls back to
+    /// `on_changing` if not set.
+    #[callback(f32)]
+    on_reset: Option<Box<dyn Fn(&mut EventContext, f32)>>,
+    #[callback]
+    on_drag_start: DragCallback,
+    #[callback]
+    on_drag_end: DragCallback,
+}
+
+impl<L> Knob<L, L>
+where
+    L: Lens<Target = f32>,
+{
+    /// Create a new `Knob`
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - Vizia `Context`
+    /// * `value` - a `vizia::Lens` specifying the value of this knob
+    /// * `range` - the arbitrary range of this knob. Ranges spanning zero,
+    ///   such as `-1f32..=1f32`, make a bipolar knob.
+    pub fn new(cx: &mut Context, value: L, range: RangeInclusive<f32>) -> Handle<Self> {
+        Self::build_knob(cx, value, None, Parameter::new(range))
+    }
+
+    /// Create a new `Knob` whose mapping and default value follow a
+    /// [`Parameter`]
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - Vizia `Context`
+    /// * `value` - a `vizia::Lens` specifying the plain value of this knob
+    /// * `param` - the definition of the value
+    pub fn with_param(cx: &mut Context, value: L, param: Parameter) -> Handle<Self> {
+        Self::build_knob(cx, value, None, param)
+    }
+}
+
+impl<L, M> Knob<L, M>
+where
+    L: Lens<Target = f32>,
+    M: Lens<Target = f32>,
+{
+    /// Create a new `Knob` that shows a modulation amount as an outer ring
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - Vizia `Context`
+    /// * `value` - a `vizia::Lens` specifying the value of this knob
+    /// * `modulation` - a `vizia::Lens` specifying the normalized modulation
+    ///   amount, from `-1f32..=1f32`
+    /// * `range` - the arbitrary range of this knob
+    pub fn with_modulation(
+        cx: &mut Context,
+        value: L,
+        modulation: M,
+        range: RangeInclusive<f32>,
+    ) -> Handle<Self> {
+        Self::build_knob(cx, value, Some(modulation), Parameter::new(range))
+    }
+
+    /// Create a new `Knob` that shows a modulation amount as an outer ring,
+    /// and whose mapping and default value follow a [`Parameter`]
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - Vizia `Context`
+    /// * `value` - a `vizia::Lens` specifying the plain value of this knob
+    /// * `modulation` - a `vizia::Lens` specifying the normalized modulation
+    ///   amount, from `-1f32..=1f32`
+    /// * `param` - the definition of the value
+    pub fn with_param_and_modulation(
+        cx: &mut Context,
+        value: L,
+        modulation: M,
+        param: Parameter,
+    ) -> Handle<Self> {
+        Self::build_knob(cx, value, Some(modulation), param)
+    }
+
+    fn build_knob(
+        cx: &mut Context,
+        value: L,
+        modulation: Option<M>,
+        param: Parameter,
+    ) -> Handle<Self> {
+        let mut classes = HashMap::<&'static str, Entity>::default();
+        let handle = Self {
+            value,
+            modulation,
+            param,
+            hover: false,
+            active: false,
+            drag_position: Vec2::ZERO,
+            drag_value: 0f32,
+            classes: HashMap::default(),
+            on_changing: None,
+            on_reset: None,
+            on_drag_start: None,
+            on_drag_end: None,
+        }
+        .build(cx, |cx| {
+            let e = Element::new(cx)
+                .class("modulation")
+                .display(Display::None)
+                .entity;
+            classes.insert("modulation", e);
+        });
+        modify_view(handle.cx, handle.entity, |knob: &mut Self| {
+            knob.classes = classes
+        });
+        handle
+    }
+
+    /// Calls `on_reset`, or `on_changing` if it isn't set, with the default
+    /// value
+    fn reset(&self, cx: &mut EventContext) {
+        let callback = self.on_reset.as_deref().or(self.on_changing.as_deref());
+        reset_to_default(cx, self.param.default, callback);
+    }
+}
+
+/// Modifiers for configuring a [`Knob`]
+pub trait KnobModifiers {
+    /// Sets the value to reset to when double-clicking or ctrl/cmd-clicking
+    /// the knob, which is also marked beside the arc
+    fn default_value(self, value: f32) -> Self;
+}
+
+impl<'a, L, M> KnobModifiers for Handle<'a, Knob<L, M>>
+where
+    L: Lens<Target = f32>,
+    M: Lens<Target = f32>,
+{
+    fn default_value(self, value: f32) -> Self {
+        modify_view(self.cx, self.entity, |knob: &mut Knob<L, M>| {
+            knob.param.default = Some(value)
+        });
+        self
+    }
+}
+
+impl<L, M> View for Knob<L, M>
+where
+    L: Lens<Target = f32>,
+    M: Lens<Target = f32>,
+{
+    fn element(&self) -> Option<&'static str> {
+        Some("knob")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|ev: &WindowEvent, _| match *ev {
+            WindowEvent::MouseEnter => self.hover = true,
+            WindowEvent::MouseLeave => self.hover = false,
+            WindowEvent::MouseDown(button) => {
+                if button == MouseButton::Left {
+                    if cx.modifiers.contains(Modifiers::CTRL)
+                        || cx.modifiers.contains(Modifiers::LOGO)
+                    {
+                        self.reset(cx);
+                        return;
+                    }
+                    self.drag_position = Vec2::new(cx.mouse.cursorx, cx.mouse.cursory);
+                    self.drag_value = self.param.normalize(self.value.get(cx));
+                    start_drag(cx, &mut self.active, &self.on_drag_start);
+                }
+            }
+            WindowEvent::MouseDoubleClick(button) => {
+                if button == MouseButton::Left {
+                    // Stop the drag started by the first click
+                    end_drag(cx, &mut self.active, &self.on_drag_end);
+                    self.reset(cx);
+                }
+            }
+            WindowEvent::MouseUp(button) => {
+                if button == MouseButton::Left {
+                    end_drag(cx, &mut self.active, &self.on_drag_end);
+                }
+            }
+            WindowEvent::MouseMove(x, y) => {
+                if self.active {
+                    // Moving up or right increases the value
+                    let delta = Vec2::new(x, y) - self.drag_position;
+                    self.drag_position = Vec2::new(x, y);
+                    let scalar = match cx.modifiers.contains(Modifiers::SHIFT) {
+                        true => FINE_SCALAR,
+                        false => 1f32,
+                    };
+                    self.drag_value = (self.drag_value
+                        + (delta.x - delta.y) / DRAG_DISTANCE * scalar)
+                        .clamp(0f32, 1f32);
+
+                    if let Some(callback) = &self.on_changing {
+                        (callback)(cx, self.param.denormalize(self.drag_value));
+                    }
+                }
+            }
+            _ => (),
+        });
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let track_color = cx.background_color().cloned().unwrap_or_default();
+        let active_color = cx.border_color().cloned().unwrap_or_default();
+        let modulation_entity = *self.classes.get("modulation").unwrap();
+        let modulation_color = cx
+            .style
+            .border_color
+            .get(modulation_entity)
+            .cloned()
+            .unwrap_or_default();
+
+        let rect = cx.bounds();
+        let center = Vec2::new(rect.x + rect.w / 2f32, rect.y + rect.h / 2f32);
+        // Leave room for the modulation ring outside of the value arc
+        let radius = rect.w.min(rect.h) / 2f32 - ARC_WIDTH * 2.5;
+        let angle = |normalized: f32| START_ANGLE + normalized.clamp(0f32, 1f32) * SWEEP_ANGLE;
+        let arc = |from: f32, to: f32, radius: f32| {
+            let mut path = Path::new();
+            path.arc(
+                center.x,
+                center.y,
+                radius,
+                angle(from.min(to)),
+                angle(from.max(to)),
+                Solidity::Hole,
+            );
+            path
+        };
+        let paint = |color: Color| {
+            let mut paint = Paint::color(color.into()).with_line_width(ARC_WIDTH);
+            paint.set_line_cap(LineCap::Round);
+            paint
+        };
+
+        let value = self.value.view(cx.data().unwrap(), |value| {
+            self.param.normalize(value.cloned().unwrap_or_default())
+        });
+        // Bipolar knobs fill from zero rather than from the start of the range
+        let range = &self.param.range;
+        let origin = match range.start().signum() != range.end().signum() {
+            true => self.param.normalize(0f32),
+            false => 0f32,
+        };
+
+        // Track
+        canvas.stroke_path(&mut arc(0f32, 1f32, radius), &paint(track_color));
+        // Value
+        canvas.stroke_path(&mut arc(origin, value, radius), &paint(active_color));
+
+        // Mark the default value just outside of the track
+        if let Some(default) = self.param.default {
+            let angle = angle(self.param.normalize(default));
+            let direction = Vec2::new(angle.cos(), angle.sin());
+            let inner = center + direction * (radius + ARC_WIDTH);
+            let outer = inner + direction * ARC_WIDTH;
+            let mut path = Path::new();
+            path.move_to(inner.x, inner.y);
+            path.line_to(outer.x, outer.y);
+            canvas.stroke_path(
+                &mut path,
+                &Paint::color(track_color.into()).with_line_width(1f32),
+            );
+        }
+
+        // Modulation ring
+        if let Some(modulation) = &self.modulation {
+            let amount = modulation.view(cx.data().unwrap(), |m| m.cloned().unwrap_or_default());
+            if amount != 0f32 {
+                let ring = radius + ARC_WIDTH * 1.5;
+                canvas.stroke_path(
+                    &mut arc(value, value + amount, ring),
+                    &paint(modulation_color),
+                );
+            }
+        }
+
+        // Pointer
+        let pointer_width = if self.active {
+            3f32
+        } else if self.hover {
+            2.5
+        } else {
+            2f32
+        };
+        let direction = Vec2::new(angle(value).cos(), angle(value).sin());
+        let (inner, outer) = (
+            center + direction * radius * 0.3,
+            center + direction * radius,
+        );
+        let mut path = Path::new();
+        path.move_to(inner.x, inner.y);
+        path.line_to(outer.x, outer.y);
+        let mut pointer = Paint::color(active_color.into()).with_line_width(pointer_width);
+        pointer.set_line_cap(LineCap::Round);
+        canvas.stroke_path(&mut path, &pointer);
+    }
+}
@@ -0,0 +1,211 @@
+use super::continuous::{ARC_WIDTH, DRAG_DISTANCE, START_ANGLE, SWEEP_ANGLE};
+use crate::util::{end_drag, start_drag, DragCallback, Steps};
+use glam::Vec2;
+use lily_derive::Handle;
+use vizia::prelude::*;
+use vizia::vg::{LineCap, Paint, Path, Solidity};
+
+/// The distance from the centre to the labels, as a fraction of the size of
+/// the knob
+const LABEL_DISTANCE: f32 = 0.4;
+/// The size of each label, as a fraction of the size of the knob
+const LABEL_SIZE: f32 = 0.3;
+/// The length in pixels of tick marks
+const TICK_LENGTH: f32 = 3f32;
+
+/// A switch-style knob that snaps between a fixed number of detents, for
+/// choices like an octave or a filter slope. Drag up or right to move to the
+/// next detent and down or left to move to the previous one.
+#[derive(Handle)]
+pub struct DiscreteKnob<L>
+where
+    L: Lens<Target = usize>,
+{
+    /// A [`Lens`] of the index of the selected option
+    value: L,
+    steps: Steps,
+    hover: bool,
+    active: bool,
+    /// The last cursor position while dragging
+    drag_position: Vec2,
+    /// The normalized position between the detents while dragging
+    drag_value: f32,
+    #[callback(usize)]
+    on_changing: Option<Box<dyn Fn(&mut EventContext, usize)>>,
+    #[callback]
+    on_drag_start: DragCallback,
+    #[callback]
+    on_drag_end: DragCallback,
+}
+
+impl<L> DiscreteKnob<L>
+where
+    L: Lens<Target = usize>,
+{
+    /// Create a new `DiscreteKnob`
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - Vizia `Context`
+    /// * `value` - a `vizia::Lens` specifying the index of the selected option
+    /// * `steps` - the detents of this knob, either a number of detents like
+    ///   `4`, drawn as tick marks, or a list of labels like `["12", "24"]`
+    ///   drawn around the arc
+    pub fn new(cx: &mut Context, value: L, steps: impl Into<Steps>) -> Handle<Self> {
+        let steps = steps.into();
+        Self {
+            value,
+            steps: steps.clone(),
+            hover: false,
+            active: false,
+            drag_position: Vec2::ZERO,
+            drag_value: 0f32,
+            on_changing: None,
+            on_drag_start: None,
+            on_drag_end: None,
+        }
+        .build(cx, |cx| {
+            if let Steps::Labels(labels) = &steps {
+                for (index, label) in labels.iter().enumerate() {
+                    // Centre each label on its detent around the arc
+                    let angle = START_ANGLE + steps.detent(index) * SWEEP_ANGLE;
+                    let center =
+                        Vec2::new(0.5, 0.5) + Vec2::new(angle.cos(), angle.sin()) * LABEL_DISTANCE;
+                    let corner = (center - LABEL_SIZE / 2f32) * 100f32;
+                    Label::new(cx, label)
+                        .position_type(PositionType::SelfDirected)
+                        .left(Percentage(corner.x))
+                        .top(Percentage(corner.y))
+                        .width(Percentage(LABEL_SIZE * 100f32))
+                        .height(Percentage(LABEL_SIZE * 100f32))
+                        .class("label");
+                }
+            }
+        })
+    }
+}
+
+impl<L> View for DiscreteKnob<L>
+where
+    L: Lens<Target = usize>,
+{
+    fn element(&self) -> Option<&'static str> {
+        Some("knob")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|ev: &WindowEvent, _| match *ev {
+            WindowEvent::MouseEnter => self.hover = true,
+            WindowEvent::MouseLeave => self.hover = false,
+            WindowEvent::MouseDown(button) => {
+                if button == MouseButton::Left {
+                    self.drag_position = Vec2::new(cx.mouse.cursorx, cx.mouse.cursory);
+                    self.drag_value = self.steps.detent(self.value.get(cx));
+                    start_drag(cx, &mut self.active, &self.on_drag_start);
+                }
+            }
+            WindowEvent::MouseUp(button) => {
+                if button == MouseButton::Left {
+                    end_drag(cx, &mut self.active, &self.on_drag_end);
+                }
+            }
+            WindowEvent::MouseMove(x, y) => {
+                if self.active {
+                    // Moving up or right moves towards the last detent
+                    let delta = Vec2::new(x, y) - self.drag_position;
+                    self.drag_position = Vec2::new(x, y);
+                    self.drag_value =
+                        (self.drag_value + (delta.x - delta.y) / DRAG_DISTANCE).clamp(0f32, 1f32);
+
+                    // Snap to the closest detent, if there are any to snap to
+                    let index = self.steps.nearest_detent(self.drag_value);
+                    if !self.steps.is_empty() && index != self.value.get(cx) {
+                        if let Some(callback) = &self.on_changing {
+                            (callback)(cx, index);
+                        }
+                    }
+                }
+            }
+            _ => (),
+        });
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let track_color = cx.background_color().cloned().unwrap_or_default();
+        let active_color = cx.border_color().cloned().unwrap_or_default();
+
+        let rect = cx.bounds();
+        let size = rect.w.min(rect.h);
+        let center = Vec2::new(rect.x + rect.w / 2f32, rect.y + rect.h / 2f32);
+        // Leave room for the labels or tick marks around the arc
+        let radius = match self.steps {
+            Steps::Labels(_) => size * (LABEL_DISTANCE - LABEL_SIZE / 2f32),
+            Steps::Count(_) => size / 2f32 - ARC_WIDTH - TICK_LENGTH * 2f32,
+        };
+        let angle = |normalized: f32| START_ANGLE + normalized * SWEEP_ANGLE;
+        let direction =
+            |normalized: f32| Vec2::new(angle(normalized).cos(), angle(normalized).sin());
+        let paint = |color: Color, width: f32| {
+            let mut paint = Paint::color(color.into()).with_line_width(width);
+            paint.set_line_cap(LineCap::Round);
+            paint
+        };
+
+        let value = self.value.view(cx.data().unwrap(), |value| {
+            self.steps.detent(value.cloned().unwrap_or_default())
+        });
+
+        // Track
+        let mut path = Path::new();
+        path.arc(
+            center.x,
+            center.y,
+            radius,
+            angle(0f32),
+            angle(1f32),
+            Solidity::Hole,
+        );
+        canvas.stroke_path(&mut path, &paint(track_color, ARC_WIDTH));
+        // Value
+        let mut path = Path::new();
+        path.arc(
+            center.x,
+            center.y,
+            radius,
+            angle(0f32),
+            angle(value),
+            Solidity::Hole,
+        );
+        canvas.stroke_path(&mut path, &paint(active_color, ARC_WIDTH));
+
+        // Tick marks for unlabelled detents
+        if let Steps::Count(count) = self.steps {
+            let mut path = Path::new();
+            for index in 0..count {
+                let direction = direction(self.steps.detent(index));
+                let inner = center + direction * (radius + ARC_WIDTH);
+                let outer = inner + direction * TICK_LENGTH;
+                path.move_to(inner.x, inner.y);
+                path.line_to(outer.x, outer.y);
+            }
+            canvas.stroke_path(&mut path, &paint(track_color, 1f32));
+        }
+
+        // Pointer
+        let pointer_width = if self.active {
+            3f32
+        } else if self.hover {
+            2.5
+        } else {
+            2f32
+        };
+        let (inner, outer) = (
+            center + direction(value) * radius * 0.3,
+            center + direction(value) * radius,
+        );
+        let mut path = Path::new();
+        path.move_to(inner.x, inner.y);
+        path.line_to(outer.x, outer.y);
+        canvas.stroke_path(&mut path, &paint(active_color, pointer_width));
+    }
+}
@@ -0,0 +1,4 @@
+mod continuous;
+mod discrete;
+pub use continuous::{Knob, KnobHandle, KnobModifiers};
+pub use discrete::{DiscreteKnob, DiscreteKnobHandle};
@@ -1,37 +1,178 @@
-use vizia::context::{Context, DrawContext};
-use vizia::events::Event;
+use crate::util::{end_drag, start_drag, DragCallback, Parameter};
+use crate::widgets::entry::{build_entry, EntryEvent};
+use glam::Vec2;
+use lily_derive::Handle;
+use std::ops::RangeInclusive;
 use vizia::prelude::*;
-use vizia::{vg, vg::renderer::OpenGl};
 
+/// The distance in pixels to drag to sweep the whole range
+const DRAG_DISTANCE: f32 = 200f32;
+/// How much slower the value changes while holding shift
+const FINE_SCALAR: f32 = 0.1;
+
+/// A label showing a value, which can be dragged up or down to change it,
+/// holding shift for finer control. Press enter or double-click it to type
+/// in an exact value.
+#[derive(Handle)]
 pub struct DragLabel<L>
 where
-    L: Lens<Target = String>,
+    L: Lens<Target = f32>,
 {
-    data: L,
+    value: L,
+    param: Parameter,
+    active: bool,
+    /// The last cursor position while dragging
+    drag_position: Vec2,
+    /// The normalized value while dragging
+    drag_value: f32,
+    #[callback(f32)]
+    on_changing: Option<Box<dyn Fn(&mut EventContext, f32)>>,
+    #[callback]
+    on_drag_start: DragCallback,
+    #[callback]
+    on_drag_end: DragCallback,
 }
 
 impl<L> DragLabel<L>
 where
-    L: Lens<Target = String>,
+    L: Lens<Target = f32>,
 {
-    pub fn new(cx: &mut Context, data: L) -> Handle<Self> {
-        Self { data }.build(cx, |_| {})
+    /// Create a new `DragLabel`, showing its value with two decimals
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - Vizia `Context`
+    /// * `value` - a `vizia::Lens` specifying the value of this label
+    /// * `range` - the arbitrary range of this label
+    pub fn new(cx: &mut Context, value: L, range: RangeInclusive<f32>) -> Handle<Self> {
+        Self::with_param(cx, value, Parameter::new(range))
+    }
+
+    /// Create a new `DragLabel` that shows its value with `format`
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - Vizia `Context`
+    /// * `value` - a `vizia::Lens` specifying the value of this label
+    /// * `range` - the arbitrary range of this label
+    /// * `format` - formats the value for display, like
+    ///   `|value| format!("{:.0} Hz", value)`
+    pub fn with_format<F>(
+        cx: &mut Context,
+        value: L,
+        range: RangeInclusive<f32>,
+        format: F,
+    ) -> Handle<Self>
+    where
+        F: 'static + Fn(f32) -> String,
+    {
+        Self::with_param(cx, value, Parameter::new(range).with_value_to_string(format))
+    }
+
+    /// Create a new `DragLabel` whose mapping, display and text entry follow a
+    /// [`Parameter`]
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - Vizia `Context`
+    /// * `value` - a `vizia::Lens` specifying the plain value of this label
+    /// * `param` - the definition of the value
+    pub fn with_param(cx: &mut Context, value: L, param: Parameter) -> Handle<Self> {
+        let format = param.clone();
+        Self {
+            value: value.clone(),
+            param,
+            active: false,
+            drag_position: Vec2::ZERO,
+            drag_value: 0f32,
+            on_changing: None,
+            on_drag_start: None,
+            on_drag_end: None,
+        }
+        .build(cx, |cx| {
+            Label::new(cx, value.map(move |value| format.format(*value)))
+                .width(Stretch(1f32))
+                .height(Stretch(1f32))
+                // Let the label handle the cursor
+                .hoverable(false);
+            build_entry(cx);
+        })
+    }
+
+    /// Clears the active style and ends the drag, if dragging
+    fn end_drag(&mut self, cx: &mut EventContext) {
+        if self.active {
+            cx.current().set_active(cx, false);
+        }
+        end_drag(cx, &mut self.active, &self.on_drag_end);
+    }
+
+    /// Calls `on_changing` with a plain value
+    fn change(&self, cx: &mut EventContext, value: f32) {
+        if let Some(callback) = &self.on_changing {
+            (callback)(cx, value);
+        }
     }
 }
 
 impl<L> View for DragLabel<L>
 where
-    L: Lens<Target = String>,
+    L: Lens<Target = f32>,
 {
     fn element(&self) -> Option<&'static str> {
         Some("lily-label")
     }
 
-    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {}
-
-    fn draw(&self, cx: &mut DrawContext, canvas: &mut vizia::vg::Canvas<OpenGl>) {
-        // let entity = cx.current();
-        // let rect = cx.cache().get_bounds(entity);
-        // TODO: Draw text
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|ev: &WindowEvent, _| match *ev {
+            WindowEvent::MouseDown(button) => {
+                if button == MouseButton::Left {
+                    // Receive keyboard events for opening the text entry
+                    cx.focus();
+                    cx.current().set_active(cx, true);
+                    self.drag_position = Vec2::new(cx.mouse.cursorx, cx.mouse.cursory);
+                    self.drag_value = self.param.normalize(self.value.get(cx));
+                    start_drag(cx, &mut self.active, &self.on_drag_start);
+                }
+            }
+            WindowEvent::MouseUp(button) => {
+                if button == MouseButton::Left {
+                    self.end_drag(cx);
+                }
+            }
+            WindowEvent::MouseMove(_, y) => {
+                if self.active {
+                    // Moving up increases the value
+                    let delta = self.drag_position.y - y;
+                    self.drag_position.y = y;
+                    let scalar = match cx.modifiers.contains(Modifiers::SHIFT) {
+                        true => FINE_SCALAR,
+                        false => 1f32,
+                    };
+                    self.drag_value =
+                        (self.drag_value + delta / DRAG_DISTANCE * scalar).clamp(0f32, 1f32);
+                    self.change(cx, self.param.denormalize(self.drag_value));
+                }
+            }
+            WindowEvent::MouseDoubleClick(button) => {
+                if button == MouseButton::Left {
+                    // Stop the drag started by the first click
+                    self.end_drag(cx);
+                    cx.emit(EntryEvent::Open(self.param.format(self.value.get(cx))));
+                }
+            }
+            WindowEvent::KeyDown(Code::Enter, _) => {
+                cx.emit(EntryEvent::Open(self.param.format(self.value.get(cx))));
+            }
+            _ => (),
+        });
+        // Ignore typed values that aren't numbers
+        event.map(|ev: &EntryEvent, _| {
+            if let EntryEvent::Submit(text) = ev {
+                if let Some(value) = self.param.parse(text) {
+                    self.change(cx, value);
+                }
+            }
+        });
     }
 }
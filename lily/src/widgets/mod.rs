@@ -1,11 +1,20 @@
+mod entry;
+mod knob;
 mod label;
 mod mseg;
 mod slider;
-// mod xy_pad;
+mod xy_pad;
 mod zoomer;
 
-pub use label::DragLabel;
-pub use mseg::{Mseg, MsegHandle};
-pub use slider::{DragSlider, DragSliderHandle};
-// pub use xy_pad::{XyPad, XyPadHandle};
+pub use knob::{DiscreteKnob, DiscreteKnobHandle, Knob, KnobHandle, KnobModifiers};
+pub use label::{DragLabel, DragLabelHandle};
+pub use mseg::{
+    grid::{MsegGrid, TimeDivision},
+    Mseg, MsegHandle, MsegModifiers,
+};
+pub use slider::{
+    DiscreteSlider, DiscreteSliderHandle, DragSlider, DragSliderHandle, DragSliderModifiers,
+    FanSlider, FanSliderHandle,
+};
+pub use xy_pad::{XyPad, XyPadHandle};
 pub use zoomer::{Zoomer, ZoomerHandle};
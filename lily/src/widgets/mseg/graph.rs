@@ -1,16 +1,37 @@
-use crate::util::CurvePoints;
+use crate::util::{
+    BoundingBoxExt, CurvePoints, DragCallback, HistoryAction, RangeExt, Viewport, MAX_CURVE,
+};
 use glam::Vec2;
 use lily_derive::Handle;
-use std::{cmp::Ordering, collections::HashMap, ops::RangeInclusive};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+};
 use vizia::prelude::*;
 use vizia::vg;
 
-use super::util::{data_to_bounds_pos_range, data_to_ui_pos_range, ui_to_data_pos_range};
+use super::grid::MsegGrid;
+use super::util::{
+    constrain_group_move, data_to_bounds_pos_range, data_to_ui_pos_range, nearest_on_curve,
+    pan_range, ui_to_data_pos_range, zoom_range,
+};
+use crate::widgets::zoomer::SMALLEST_RANGE;
 
 /// The distance in pixels before a node is considered hovered
 const HOVER_RADIUS: f32 = 16f32;
 /// The distance in seconds before two points cannot get closer
 const MIN_RESOLUTION: f32 = 0.01f32;
+/// The approximate distance in pixels between the sampled points of a curved
+/// segment
+const CURVE_STEP: f32 = 4f32;
+/// How much a segment's curve changes per pixel of vertical drag
+const CURVE_DRAG_SCALAR: f32 = 0.05f32;
+/// The smallest distance in pixels between drawn grid lines
+const MIN_GRID_SPACING: f32 = 4f32;
+/// How much the view zooms in per line scrolled
+const ZOOM_PER_SCROLL: f32 = 0.9f32;
+/// How much of the visible range the view pans per line scrolled
+const PAN_PER_SCROLL: f32 = 0.1f32;
 
 /// The visuals of the graph
 #[allow(clippy::type_complexity)]
@@ -18,25 +39,44 @@ const MIN_RESOLUTION: f32 = 0.01f32;
 pub(crate) struct MsegGraph<P, R>
 where
     P: Lens<Target = CurvePoints>,
-    R: Lens<Target = RangeInclusive<f32>>,
+    R: Lens<Target = Viewport>,
 {
     /// A [`Lens`] of type `P` representing the points on an envelope. Points
     /// have a minimum and maximum float range of (0,0) and (inf, 1)
     /// respectively
     points: P,
     /// A [`Lens`] of type `R` representing the section of the graph of which we
-    /// are zoomed. This can be any set of numbers between 0 and 1 inclusive
-    /// where the start is less than the end.
-    range: R,
+    /// are zoomed on both axes
+    viewport: R,
     /// the max `x`, in `f32` seconds, of the envelope visualization. For
     /// example, if the max is `8.0`, the maximum length of the envelope is then
     /// 8 seconds.
     max: f32,
+    /// The grid drawn behind the envelope and snapped to while dragging
+    pub(crate) grid: MsegGrid,
     /// The index of the currently hovered or pressed graph point
     active_point_id: Option<usize>,
     classes: HashMap<&'static str, Entity>,
     /// Whether we are in the process of dragging a graph point
     is_dragging_point: bool,
+    /// The index of the point whose curve handle is hovered or pressed
+    active_curve_id: Option<usize>,
+    /// The cursor `y` and curve value when we started dragging a curve handle
+    curve_drag_start: Option<(f32, f32)>,
+    /// The index and data position a point would be inserted at when
+    /// hovering near the curve
+    ghost_point: Option<(usize, Vec2)>,
+    /// The indices of the selected points
+    selection: BTreeSet<usize>,
+    /// The UI positions of the corners of the selection rectangle while box
+    /// selecting
+    box_select: Option<(Vec2, Vec2)>,
+    /// The cursor data position and the starting positions of the selected
+    /// points when dragging a selection of more than one point
+    group_drag: Option<(Vec2, Vec<(usize, Vec2)>)>,
+    /// The cursor position and the viewport when we started panning with the
+    /// middle mouse button
+    pan_start: Option<(Vec2, Viewport)>,
 
     #[callback(usize, Vec2)]
     on_changing_point: Option<Box<dyn Fn(&mut EventContext, usize, Vec2)>>,
@@ -46,12 +86,30 @@ where
 
     #[callback(usize, Vec2)]
     on_insert_point: Option<Box<dyn Fn(&mut EventContext, usize, Vec2)>>,
+
+    #[callback(usize, f32)]
+    on_changing_curve: Option<Box<dyn Fn(&mut EventContext, usize, f32)>>,
+
+    #[callback(Vec<(usize, Vec2)>)]
+    on_changing_points: Option<Box<dyn Fn(&mut EventContext, Vec<(usize, Vec2)>)>>,
+
+    /// Called with the new viewport when zooming or panning the graph itself
+    #[callback(Viewport)]
+    on_changing_viewport: Option<Box<dyn Fn(&mut EventContext, Viewport)>>,
+
+    /// Called when a point or curve starts being dragged, before any changes
+    #[callback]
+    on_drag_start: DragCallback,
+
+    /// Called when a point or curve is released
+    #[callback]
+    on_drag_end: DragCallback,
 }
 
 impl<P, R> MsegGraph<P, R>
 where
     P: Lens<Target = CurvePoints>,
-    R: Lens<Target = RangeInclusive<f32>>,
+    R: Lens<Target = Viewport>,
 {
     /// Create a new `MsegGraph`
     ///
@@ -61,29 +119,42 @@ where
     /// * `points` - a [`Lens`] with a target of [`CurvePoints`] representing
     ///   the points on an envelope. Points have a minimum and maximum float
     ///   range of (0,0) and (inf, 1) respectively
-    /// * `range` - a [`Lens`] with a target of [`RangeInclusive<f32>`]
-    ///   representing the section of the graph of which we are zoomed. This can
-    ///   be any set of numbers between 0 and 1 inclusive where the start is
-    ///   less than the end.
+    /// * `viewport` - a [`Lens`] with a target of [`Viewport`] representing
+    ///   the section of the graph of which we are zoomed on both axes
     /// * `max` - the max `x`, in `f32` seconds, of the envelope visualization.
     ///   For example, if the max is `8.0`, the maximum length of the envelope
     ///   is then 8 seconds.
-    pub fn new(cx: &mut Context, points: P, range: R, max: f32) -> Handle<MsegGraph<P, R>> {
+    pub fn new(cx: &mut Context, points: P, viewport: R, max: f32) -> Handle<MsegGraph<P, R>> {
         let mut classes = HashMap::<&'static str, Entity>::default();
         let mut insert_color = |name| {
             let e = Element::new(cx).class(name).display(Display::None).entity;
             classes.insert(name, e);
         };
         insert_color("point");
+        insert_color("selection");
+        insert_color("grid");
         Self {
             points,
             max,
+            grid: MsegGrid::default(),
             active_point_id: None,
             is_dragging_point: false,
+            active_curve_id: None,
+            curve_drag_start: None,
+            ghost_point: None,
+            selection: BTreeSet::default(),
+            box_select: None,
+            group_drag: None,
+            pan_start: None,
             on_changing_point: None,
-            range,
+            viewport,
             on_remove_point: None,
             on_insert_point: None,
+            on_changing_curve: None,
+            on_changing_points: None,
+            on_changing_viewport: None,
+            on_drag_start: None,
+            on_drag_end: None,
             classes,
         }
         .build(cx, |_cx| {})
@@ -93,7 +164,7 @@ where
 impl<P, R> View for MsegGraph<P, R>
 where
     P: Lens<Target = CurvePoints>,
-    R: Lens<Target = RangeInclusive<f32>>,
+    R: Lens<Target = Viewport>,
 {
     fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
         let points = self.points.get(cx);
@@ -103,7 +174,7 @@ where
                 data_to_ui_pos_range(
                     cx,
                     Vec2::new(point.x, point.y),
-                    self.range.clone(),
+                    self.viewport.clone(),
                     self.max,
                 )
             })
@@ -113,24 +184,104 @@ where
             WindowEvent::MouseDown(button) => {
                 match button {
                     MouseButton::Left => {
+                        // Receive keyboard events for selection shortcuts
+                        cx.focus();
+                        let shift = cx.modifiers.contains(Modifiers::SHIFT);
                         // TODO: only set active point if cursor is within the element.
                         // Right now it will activate even if the cursor is off the element.
-                        if self.active_point_id.is_some() {
+                        if let Some(index) = self.active_point_id {
+                            if shift {
+                                // Toggle the point in the selection
+                                if !self.selection.remove(&index) {
+                                    self.selection.insert(index);
+                                }
+                            } else {
+                                if !self.selection.contains(&index) {
+                                    self.selection = BTreeSet::from([index]);
+                                }
+                                cx.capture();
+                                self.is_dragging_point = true;
+                                if let Some(callback) = &self.on_drag_start {
+                                    (callback)(cx);
+                                }
+                                // Drag all selected points together
+                                if self.selection.len() > 1 {
+                                    let cursor = ui_to_data_pos_range(
+                                        cx,
+                                        &Vec2::new(cx.mouse.cursorx, cx.mouse.cursory),
+                                        self.viewport.clone(),
+                                        self.max,
+                                    );
+                                    let origins = self
+                                        .selection
+                                        .iter()
+                                        .filter_map(|i| {
+                                            points.get(*i).map(|p| (*i, Vec2::new(p.x, p.y)))
+                                        })
+                                        .collect();
+                                    self.group_drag = Some((cursor, origins));
+                                }
+                            }
+                        } else if let Some(index) = self.active_curve_id {
                             cx.capture();
-                            self.is_dragging_point = true;
+                            self.curve_drag_start = Some((cx.mouse.cursory, points[index].curve));
+                            if let Some(callback) = &self.on_drag_start {
+                                (callback)(cx);
+                            }
+                        } else if let Some((index, point)) = self.ghost_point.take() {
+                            // Insert a point on the curve and start dragging it
+                            if let Some(callback) = &self.on_insert_point {
+                                if let Some(on_drag_start) = &self.on_drag_start {
+                                    (on_drag_start)(cx);
+                                }
+                                (callback)(cx, index, point);
+                                cx.capture();
+                                self.active_point_id = Some(index);
+                                self.is_dragging_point = true;
+                                // Indices have shifted, so select only the new point
+                                self.selection = BTreeSet::from([index]);
+                            }
                         } else {
-                            // TODO: create a new point
+                            // Start a selection rectangle, adding to the current
+                            // selection if holding shift
+                            cx.capture();
+                            let cursor = Vec2::new(cx.mouse.cursorx, cx.mouse.cursory);
+                            self.box_select = Some((cursor, cursor));
+                            if !shift {
+                                self.selection.clear();
+                            }
                         }
                     }
                     MouseButton::Right => {
                         // Delete a currently active point
                         if let Some(index) = self.active_point_id {
                             cx.release();
+                            // Close the gesture when deleting the dragged point
+                            if self.is_dragging_point {
+                                if let Some(callback) = &self.on_drag_end {
+                                    (callback)(cx);
+                                }
+                            }
                             self.is_dragging_point = false;
+                            self.group_drag = None;
+                            // Indices are about to shift
+                            self.selection.clear();
                             if let Some(callback) = &self.on_remove_point {
                                 (callback)(cx, index);
                             }
                         }
+                        // Straighten a currently active curve
+                        else if let Some(index) = self.active_curve_id {
+                            if let Some(callback) = &self.on_changing_curve {
+                                (callback)(cx, index, 0f32);
+                            }
+                        }
+                    }
+                    MouseButton::Middle => {
+                        // Pan the view by dragging
+                        cx.capture();
+                        let cursor = Vec2::new(cx.mouse.cursorx, cx.mouse.cursory);
+                        self.pan_start = Some((cursor, self.viewport.get(cx)));
                     }
                     _ => (),
                 }
@@ -138,9 +289,28 @@ where
             // Release the current context and signal that we are no longer
             // dragging a point
             WindowEvent::MouseUp(button) => {
+                if button == MouseButton::Middle && self.pan_start.take().is_some() {
+                    cx.release();
+                }
                 if button == MouseButton::Left {
                     cx.release();
+                    if self.is_dragging_point || self.curve_drag_start.is_some() {
+                        if let Some(callback) = &self.on_drag_end {
+                            (callback)(cx);
+                        }
+                    }
                     self.is_dragging_point = false;
+                    self.curve_drag_start = None;
+                    self.group_drag = None;
+                    // Select all points within the selection rectangle
+                    if let Some((start, end)) = self.box_select.take() {
+                        let (min, max) = (start.min(end), start.max(end));
+                        self.selection.extend(ui_points.iter().enumerate().filter_map(
+                            |(i, point)| {
+                                (point.cmpge(min).all() && point.cmple(max).all()).then_some(i)
+                            },
+                        ));
+                    }
                 }
             }
             // Perform dragging actions depending on state
@@ -148,12 +318,56 @@ where
                 let current_pos = Vec2::new(x, y);
                 // Drag around the point to match the current cursor
                 // position
-                if self.is_dragging_point {
+                // Snap to the grid unless holding alt
+                let snap = !cx.modifiers.contains(Modifiers::ALT);
+                // Pan so that the grabbed point stays under the cursor
+                if let Some((start, start_viewport)) = &self.pan_start {
+                    if let Some(callback) = &self.on_changing_viewport {
+                        let bounds = cx.cache.get_bounds(cx.current());
+                        let delta_x = (start.x - x) / bounds.w * start_viewport.x.width();
+                        let delta_y = (y - start.y) / bounds.h * start_viewport.y.width();
+                        (callback)(
+                            cx,
+                            Viewport::new(
+                                pan_range(&start_viewport.x, delta_x),
+                                pan_range(&start_viewport.y, delta_y),
+                            ),
+                        );
+                    }
+                } else if let (true, Some((start, origins))) =
+                    (self.is_dragging_point, &self.group_drag)
+                {
+                    if let Some(callback) = &self.on_changing_points {
+                        let mut delta =
+                            ui_to_data_pos_range(cx, &current_pos, self.viewport.clone(), self.max)
+                                - *start;
+                        // Snap the grabbed point and move the others with it
+                        let grabbed = origins
+                            .iter()
+                            .find(|(i, _)| Some(*i) == self.active_point_id)
+                            .map(|(_, origin)| *origin);
+                        if let (true, Some(origin)) = (snap, grabbed) {
+                            delta = self.grid.snap(origin + delta) - origin;
+                        }
+                        let moves =
+                            constrain_group_move(&points, origins, delta, self.max, MIN_RESOLUTION);
+                        (callback)(cx, moves);
+                    }
+                } else if self.is_dragging_point {
                     // Up to the user to drag the current point around
                     if let Some(callback) = &self.on_changing_point {
                         let active_id = self.active_point_id.unwrap();
                         let mut new_v = if active_id != 0 {
-                            ui_to_data_pos_range(cx, &current_pos, self.range.clone(), self.max)
+                            let new_v = ui_to_data_pos_range(
+                                cx,
+                                &current_pos,
+                                self.viewport.clone(),
+                                self.max,
+                            );
+                            match snap {
+                                true => self.grid.snap(new_v),
+                                false => new_v,
+                            }
                         } else {
                             Vec2::ZERO
                         };
@@ -174,6 +388,26 @@ where
                         (callback)(cx, active_id, new_v);
                     }
                 }
+                // Resize the selection rectangle
+                else if let Some((start, _)) = self.box_select {
+                    self.box_select = Some((start, current_pos));
+                }
+                // Bend the active curve by dragging vertically
+                else if let (Some(index), Some((start_y, start_curve))) =
+                    (self.active_curve_id, self.curve_drag_start)
+                {
+                    if let Some(callback) = &self.on_changing_curve {
+                        // Flip the direction for falling segments so that
+                        // dragging up always bends the curve up
+                        let direction = match points[index].y < points[index - 1].y {
+                            true => -1f32,
+                            false => 1f32,
+                        };
+                        let curve = (start_curve - (start_y - y) * CURVE_DRAG_SCALAR * direction)
+                            .clamp(-MAX_CURVE, MAX_CURVE);
+                        (callback)(cx, index, curve);
+                    }
+                }
                 // If not dragging, perform some other checks
                 else {
                     // determine if we are hovering within the range of a
@@ -206,9 +440,111 @@ where
                         }
                         _ => self.active_point_id = None,
                     }
+                    // Points take priority over the curve handles between
+                    // them
+                    self.active_curve_id = match self.active_point_id {
+                        Some(_) => None,
+                        None => curve_handles(&points)
+                            .map(|(i, handle)| {
+                                let ui_handle = data_to_ui_pos_range(
+                                    cx,
+                                    handle,
+                                    self.viewport.clone(),
+                                    self.max,
+                                );
+                                (i, ui_handle.distance_squared(current_pos))
+                            })
+                            .filter(|(_, distance)| *distance <= HOVER_RADIUS.powi(2))
+                            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+                            .map(|(i, _)| i),
+                    };
+                    // Show where a point would be inserted if hovering near
+                    // the curve between points
+                    self.ghost_point = None;
+                    let bounds = cx.cache.get_bounds(cx.current());
+                    if self.active_point_id.is_none()
+                        && self.active_curve_id.is_none()
+                        && bounds.contains_point(current_pos)
+                    {
+                        // Measure the distance to the curve as it is drawn, so
+                        // that steep segments are as easy to hit as flat ones
+                        let viewport = self.viewport.get(cx);
+                        let to_ui = |point| {
+                            data_to_bounds_pos_range(bounds, point, viewport.clone(), self.max)
+                        };
+                        if let Some((index, point)) =
+                            nearest_on_curve(&points, current_pos, HOVER_RADIUS, CURVE_STEP, to_ui)
+                        {
+                            let fits = point.x - points[index].x >= MIN_RESOLUTION
+                                && points[index + 1].x - point.x >= MIN_RESOLUTION;
+                            if fits {
+                                self.ghost_point = Some((index + 1, point));
+                            }
+                        }
+                    }
+                }
+            }
+            // Zoom around the cursor with the scroll wheel, or pan with
+            // horizontal or shift scrolling. Trackpad pinches usually arrive
+            // as ctrl+scroll, so they zoom as well. Holding alt zooms the
+            // value axis instead of the time axis.
+            WindowEvent::MouseScroll(scroll_x, scroll_y) => {
+                if let Some(callback) = &self.on_changing_viewport {
+                    let viewport = self.viewport.get(cx);
+                    let (scroll_x, scroll_y) = match cx.modifiers.contains(Modifiers::SHIFT) {
+                        true => (scroll_x + scroll_y, 0f32),
+                        false => (scroll_x, scroll_y),
+                    };
+                    let mut new_viewport = viewport.clone();
+                    if scroll_y != 0f32 {
+                        let bounds = cx.cache.get_bounds(cx.current());
+                        let factor = ZOOM_PER_SCROLL.powf(scroll_y);
+                        if cx.modifiers.contains(Modifiers::ALT) {
+                            let anchor = ((bounds.y + bounds.h - cx.mouse.cursory) / bounds.h)
+                                .clamp(0f32, 1f32);
+                            new_viewport.y =
+                                zoom_range(&new_viewport.y, anchor, factor, SMALLEST_RANGE);
+                        } else {
+                            let anchor =
+                                ((cx.mouse.cursorx - bounds.x) / bounds.w).clamp(0f32, 1f32);
+                            new_viewport.x =
+                                zoom_range(&new_viewport.x, anchor, factor, SMALLEST_RANGE);
+                        }
+                    }
+                    if scroll_x != 0f32 {
+                        let delta = -scroll_x * PAN_PER_SCROLL * new_viewport.x.width();
+                        new_viewport.x = pan_range(&new_viewport.x, delta);
+                    }
+                    if new_viewport != viewport {
+                        (callback)(cx, new_viewport);
+                    }
+                }
+            }
+            WindowEvent::KeyDown(code, _) => match code {
+                // Select all
+                Code::KeyA
+                    if cx.modifiers.contains(Modifiers::CTRL)
+                        || cx.modifiers.contains(Modifiers::LOGO) =>
+                {
+                    self.selection = (0..points.len()).collect();
+                }
+                Code::Escape => self.selection.clear(),
+                // Undoing and redoing replace the points, so the selected
+                // indices may no longer refer to the same points
+                _ if HistoryAction::from_key(code, *cx.modifiers).is_some() => {
+                    self.selection.clear();
+                    self.group_drag = None;
+                }
+                _ => (),
+            },
+            // Clear any hover state, unless we are still dragging
+            WindowEvent::MouseLeave => {
+                if !self.is_dragging_point && self.curve_drag_start.is_none() {
+                    self.active_point_id = None;
+                    self.active_curve_id = None;
+                    self.ghost_point = None;
                 }
             }
-            // WindowEvent::MouseOut => todo!(),
             _ => (),
         });
     }
@@ -216,37 +552,57 @@ where
         let default_color: Color = cx.border_color().copied().unwrap_or_default();
 
         // points
-        let range = self
-            .range
-            .view(cx.data().unwrap(), |range| range.unwrap().clone());
+        let viewport = self
+            .viewport
+            .view(cx.data().unwrap(), |viewport| viewport.unwrap().clone());
         let bounds = cx.bounds();
+        // Keep anything zoomed out of view from drawing over its neighbours
+        canvas.save();
+        canvas.scissor(bounds.x, bounds.y, bounds.w, bounds.h);
         self.points.view(cx.data().unwrap(), |points| {
             let points = points.unwrap();
+            let to_ui =
+                |point: Vec2| data_to_bounds_pos_range(bounds, point, viewport.clone(), self.max);
             let ui_points: Vec<(_, _)> = points
                 .iter()
                 .enumerate()
-                .map(|point| {
-                    (
-                        point.0,
-                        data_to_bounds_pos_range(
-                            bounds,
-                            Vec2::new(point.1.x, point.1.y),
-                            range.clone(),
-                            self.max,
-                        ),
-                    )
-                })
+                .map(|point| (point.0, to_ui(Vec2::new(point.1.x, point.1.y))))
                 .collect();
 
-            // Draw lines
-            let mut lines = vg::Path::new();
-            for (i, point) in &ui_points {
-                if i == &0 {
-                    lines.move_to(point.x, point.y);
+            // Draw the grid behind everything else, skipping divisions too small
+            // to be useful at this zoom
+            let grid_entity = *self.classes.get("grid").unwrap();
+            let grid_color = cx.style.border_color.get(grid_entity).copied().unwrap_or_default();
+            let mut grid = vg::Path::new();
+            let (start, end) = (viewport.x.start() * self.max, viewport.x.end() * self.max);
+            let time_spacing = self.grid.time.map(|t| t.seconds()).unwrap_or_default()
+                / (end - start)
+                * bounds.w;
+            if time_spacing >= MIN_GRID_SPACING {
+                for x in self.grid.time_lines(start, end) {
+                    let x = to_ui(Vec2::new(x, 0f32)).x;
+                    grid.move_to(x, bounds.top());
+                    grid.line_to(x, bounds.bottom());
+                }
+            }
+            let value_spacing = bounds.h
+                / self.grid.value_steps.unwrap_or_default() as f32
+                / viewport.y.width();
+            if value_spacing >= MIN_GRID_SPACING {
+                for y in self.grid.value_lines() {
+                    let y = to_ui(Vec2::new(0f32, y)).y;
+                    grid.move_to(bounds.left(), y);
+                    grid.line_to(bounds.right(), y);
                 }
-                // Lines
-                lines.line_to(point.x, point.y);
             }
+            canvas.stroke_path(
+                &mut grid,
+                &vg::Paint::color(grid_color.into()).with_line_width(1f32),
+            );
+
+            // Draw curves, sampling each segment so that it matches the
+            // evaluated envelope
+            let mut lines = envelope_path(points, &to_ui);
             canvas.stroke_path(
                 &mut lines,
                 &vg::Paint::color(default_color.into()).with_line_width(2f32),
@@ -272,6 +628,10 @@ where
                         &mut path,
                         &vg::Paint::color(active_point_color.into()).with_line_width(2f32),
                     );
+                } else if self.selection.contains(i) {
+                    let mut path = vg::Path::new();
+                    path.circle(point.x, point.y, 5.0);
+                    canvas.fill_path(&mut path, &vg::Paint::color(active_point_color.into()));
                 } else {
                     let mut path = vg::Path::new();
                     path.circle(point.x, point.y, 4.0);
@@ -279,13 +639,85 @@ where
                 }
             }
 
-            // check to see if we are hovering near an interpolated point
-            if self.active_point_id.is_none() {
-                // TODO:  todo!()
-                // let mouse = Vec2::new(cx.mouse.cursorx, cx.mouse.cursory); let
-                // mouse_data_pos = ui_to_data_pos(cx, &mouse, self.range,
-                // self.max); let point_at_x = lerp(left., right.y, normalized);
+            // Draw the selection rectangle
+            if let Some((start, end)) = self.box_select {
+                let selection_entity = *self.classes.get("selection").unwrap();
+                let fill = cx
+                    .style
+                    .background_color
+                    .get(selection_entity)
+                    .copied()
+                    .unwrap_or_default();
+                let border =
+                    cx.style.border_color.get(selection_entity).copied().unwrap_or_default();
+                let (min, size) = (start.min(end), (end - start).abs());
+                let mut path = vg::Path::new();
+                path.rect(min.x, min.y, size.x, size.y);
+                canvas.fill_path(&mut path, &vg::Paint::color(fill.into()));
+                canvas.stroke_path(
+                    &mut path,
+                    &vg::Paint::color(border.into()).with_line_width(1f32),
+                );
+            }
+
+            // Curve handles
+            for (i, handle) in curve_handles(points) {
+                let handle = to_ui(handle);
+                let mut path = vg::Path::new();
+                path.circle(handle.x, handle.y, 3.0);
+                if self.active_curve_id == Some(i) {
+                    canvas.fill_path(&mut path, &vg::Paint::color(active_point_color.into()));
+                } else {
+                    canvas.stroke_path(
+                        &mut path,
+                        &vg::Paint::color(point_color.into()).with_line_width(1f32),
+                    );
+                }
+            }
+
+            // Draw the point we would insert when hovering near the curve
+            if let Some((_, point)) = self.ghost_point {
+                let point = to_ui(point);
+                let mut path = vg::Path::new();
+                path.circle(point.x, point.y, 4.0);
+                canvas.stroke_path(
+                    &mut path,
+                    &vg::Paint::color(point_color.into()).with_line_width(2f32),
+                );
             }
         });
+        canvas.restore();
+    }
+}
+
+/// Gets the data position of the curve handle of every segment, paired with the
+/// index of the point whose curve it controls. Handles sit on the curve at the
+/// horizontal midpoint of their segment.
+fn curve_handles(points: &CurvePoints) -> impl Iterator<Item = (usize, Vec2)> + '_ {
+    points.windows(2).enumerate().map(move |(i, pair)| {
+        let x = (pair[0].x + pair[1].x) / 2f32;
+        (i + 1, Vec2::new(x, points.segment_value(i, x)))
+    })
+}
+
+/// Builds a path through `points`, mapped to the UI with `to_ui`. Curved
+/// segments are sampled so that the path matches the evaluated envelope.
+pub(crate) fn envelope_path(points: &CurvePoints, to_ui: impl Fn(Vec2) -> Vec2) -> vg::Path {
+    let mut path = vg::Path::new();
+    let ui_points: Vec<Vec2> = points.iter().map(|p| to_ui(Vec2::new(p.x, p.y))).collect();
+    for (i, point) in ui_points.iter().enumerate() {
+        if i == 0 {
+            path.move_to(point.x, point.y);
+            continue;
+        }
+        let (left, right) = (points[i - 1], points[i]);
+        let steps = ((point.x - ui_points[i - 1].x) / CURVE_STEP).ceil().max(1f32);
+        for step in 1..steps as usize {
+            let x = left.x + (right.x - left.x) * (step as f32 / steps);
+            let sampled = to_ui(Vec2::new(x, points.segment_value(i - 1, x)));
+            path.line_to(sampled.x, sampled.y);
+        }
+        path.line_to(point.x, point.y);
     }
+    path
 }
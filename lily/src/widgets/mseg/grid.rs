@@ -0,0 +1,140 @@
+//! Grid lines and snapping for the MSEG
+
+use glam::Vec2;
+use vizia::prelude::Data;
+
+/// The spacing between vertical grid lines
+#[derive(Clone, Copy, Debug, PartialEq, Data)]
+pub enum TimeDivision {
+    /// A fixed number of seconds
+    Seconds(f32),
+    /// A number of beats at a tempo, for envelopes synced to the host
+    Beats { bpm: f32, beats: f32 },
+}
+
+impl TimeDivision {
+    /// The length of the division in seconds
+    pub fn seconds(&self) -> f32 {
+        match *self {
+            TimeDivision::Seconds(seconds) => seconds,
+            TimeDivision::Beats { bpm, beats } => beats * 60f32 / bpm,
+        }
+    }
+}
+
+/// A grid drawn behind an [`Mseg`](super::Mseg) that points snap to while
+/// being dragged
+#[derive(Clone, Copy, Debug, Default, PartialEq, Data)]
+pub struct MsegGrid {
+    /// The spacing of vertical lines along the time axis
+    pub time: Option<TimeDivision>,
+    /// The number of steps the value axis from `0..=1` is divided into
+    pub value_steps: Option<u32>,
+}
+
+impl MsegGrid {
+    /// A grid with vertical lines every `seconds`
+    pub fn seconds(seconds: f32) -> Self {
+        Self {
+            time: Some(TimeDivision::Seconds(seconds)),
+            value_steps: None,
+        }
+    }
+
+    /// A grid with vertical lines every `beats` at `bpm`
+    pub fn beats(bpm: f32, beats: f32) -> Self {
+        Self {
+            time: Some(TimeDivision::Beats { bpm, beats }),
+            value_steps: None,
+        }
+    }
+
+    /// Divides the value axis into `steps` steps
+    pub fn with_value_steps(mut self, steps: u32) -> Self {
+        self.value_steps = Some(steps);
+        self
+    }
+
+    /// The time division in seconds, if it is usable
+    fn time_step(&self) -> Option<f32> {
+        self.time
+            .map(|time| time.seconds())
+            .filter(|step| step.is_finite() && *step > 0f32)
+    }
+
+    /// Snaps a data point to the nearest grid intersection. Axes without
+    /// divisions are left as they are.
+    pub fn snap(&self, point: Vec2) -> Vec2 {
+        let x = match self.time_step() {
+            Some(step) => (point.x / step).round() * step,
+            None => point.x,
+        };
+        let y = match self.value_steps.filter(|steps| *steps > 0) {
+            Some(steps) => (point.y * steps as f32).round() / steps as f32,
+            None => point.y,
+        };
+        Vec2::new(x, y)
+    }
+
+    /// The `x` of every vertical line from `start..=end` seconds
+    pub fn time_lines(&self, start: f32, end: f32) -> impl Iterator<Item = f32> {
+        let (first, last, step) = match self.time_step() {
+            Some(step) => ((start / step).ceil() as i64, (end / step).floor() as i64, step),
+            None => (1, 0, 0f32),
+        };
+        (first..=last).map(move |i| i as f32 * step)
+    }
+
+    /// The `y` of every horizontal line between `0` and `1`
+    pub fn value_lines(&self) -> impl Iterator<Item = f32> {
+        let steps = self.value_steps.unwrap_or_default();
+        (1..steps).map(move |i| i as f32 / steps as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn beats_to_seconds() {
+        let division = TimeDivision::Beats {
+            bpm: 120f32,
+            beats: 1f32,
+        };
+        assert_approx_eq!(division.seconds(), 0.5);
+        let division = TimeDivision::Beats {
+            bpm: 90f32,
+            beats: 0.25,
+        };
+        assert_approx_eq!(division.seconds(), 1f32 / 6f32);
+    }
+
+    #[test]
+    fn snaps_point() {
+        let grid = MsegGrid::beats(120f32, 1f32).with_value_steps(4);
+        let snapped = grid.snap(Vec2::new(1.3, 0.3));
+        assert_approx_eq!(snapped.x, 1.5);
+        assert_approx_eq!(snapped.y, 0.25);
+    }
+
+    #[test]
+    fn snaps_only_divided_axes() {
+        let point = Vec2::new(1.3, 0.3);
+        assert_eq!(MsegGrid::default().snap(point), point);
+        let snapped = MsegGrid::seconds(1f32).snap(point);
+        assert_approx_eq!(snapped.x, 1f32);
+        assert_approx_eq!(snapped.y, 0.3);
+    }
+
+    #[test]
+    fn gets_lines() {
+        let grid = MsegGrid::seconds(0.5).with_value_steps(4);
+        let lines: Vec<f32> = grid.time_lines(0.2, 2f32).collect();
+        assert_eq!(lines, vec![0.5, 1.0, 1.5, 2.0]);
+        let lines: Vec<f32> = grid.value_lines().collect();
+        assert_eq!(lines, vec![0.25, 0.5, 0.75]);
+        assert_eq!(MsegGrid::default().time_lines(0f32, 8f32).count(), 0);
+    }
+}
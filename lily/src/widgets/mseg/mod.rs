@@ -1,25 +1,37 @@
 //! Multi-stage envelope generator widget
 
 pub(crate) mod graph;
+pub mod grid;
+pub(crate) mod ruler;
 pub(crate) mod util;
 
-use self::graph::{MsegGraph, MsegGraphHandle};
-use std::{marker::PhantomData, ops::RangeInclusive};
+use self::graph::{envelope_path, MsegGraph, MsegGraphHandle};
+use self::grid::MsegGrid;
+use self::ruler::{MsegRuler, MsegValueAxis, VALUE_AXIS_WIDTH};
+use self::util::data_to_bounds_pos_range;
+use std::ops::RangeInclusive;
 
-use super::zoomer::{Zoomer, ZoomerHandle};
-use crate::util::CurvePoints;
+use super::zoomer::{Zoomer, ZoomerHandle, ZOOMER_SIZE};
+use crate::util::{modify_view, CurvePoints, DragCallback, History, HistoryAction, Viewport};
 use glam::Vec2;
 use lily_derive::Handle;
 use vizia::prelude::*;
+use vizia::vg;
 
 #[allow(clippy::enum_variant_names)]
 enum MsegInternalEvent {
     OnChangingRangeStart(f32),
     OnChangingRangeEnd(f32),
     OnChangingRangeBoth { start: f32, end: f32 },
+    OnChangingRangeY(RangeInclusive<f32>),
+    OnChangingViewport(Viewport),
     OnChangingPoint { index: usize, point: Vec2 },
     OnRemovePoint { index: usize },
     OnInsertPoint { index: usize, point: Vec2 },
+    OnChangingCurve { index: usize, curve: f32 },
+    OnChangingPoints(Vec<(usize, Vec2)>),
+    OnDragStart,
+    OnDragEnd,
 }
 
 #[allow(clippy::type_complexity)]
@@ -27,10 +39,14 @@ enum MsegInternalEvent {
 pub struct Mseg<P, R>
 where
     P: Lens<Target = CurvePoints>,
-    R: Lens<Target = RangeInclusive<f32>>,
+    R: Lens<Target = Viewport>,
 {
     points: P,
-    range: PhantomData<R>,
+    viewport: R,
+    /// Snapshots of the points before each edit, for undo and redo
+    history: History<CurvePoints>,
+    /// The entity of the graph, for passing on modifiers
+    graph: Entity,
 
     #[callback(usize)]
     on_remove_point: Option<Box<dyn Fn(&mut EventContext, usize)>>,
@@ -41,6 +57,17 @@ where
     #[callback(usize, Vec2)]
     on_changing_point: Option<Box<dyn Fn(&mut EventContext, usize, Vec2)>>,
 
+    #[callback(usize, f32)]
+    on_changing_curve: Option<Box<dyn Fn(&mut EventContext, usize, f32)>>,
+
+    #[callback(Vec<(usize, Vec2)>)]
+    on_changing_points: Option<Box<dyn Fn(&mut EventContext, Vec<(usize, Vec2)>)>>,
+
+    /// Called with the points to replace the current points with when undoing
+    /// or redoing
+    #[callback(CurvePoints)]
+    on_restore_points: Option<Box<dyn Fn(&mut EventContext, CurvePoints)>>,
+
     #[callback(f32)]
     on_changing_range_start: Option<Box<dyn Fn(&mut EventContext, f32)>>,
 
@@ -49,41 +76,163 @@ where
 
     #[callback(RangeInclusive<f32>)]
     on_changing_range_both: Option<Box<dyn Fn(&mut EventContext, RangeInclusive<f32>)>>,
+
+    /// Called with the new vertical range when zooming the value axis
+    #[callback(RangeInclusive<f32>)]
+    on_changing_range_y: Option<Box<dyn Fn(&mut EventContext, RangeInclusive<f32>)>>,
+
+    /// Called when a point or curve starts being dragged, before any changes
+    #[callback]
+    on_drag_start: DragCallback,
+
+    /// Called when a point or curve is released
+    #[callback]
+    on_drag_end: DragCallback,
 }
 
 impl<P, R> Mseg<P, R>
 where
     P: Lens<Target = CurvePoints>,
-    R: Lens<Target = RangeInclusive<f32>>,
+    R: Lens<Target = Viewport>,
 {
-    pub fn new(cx: &mut Context, points: P, range: R, max: f32) -> Handle<Mseg<P, R>> {
-        Self {
+    /// Create a new `Mseg`
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - the current [`Context`]
+    /// * `points` - a [`Lens`] with a target of [`CurvePoints`] representing
+    ///   the points on the envelope
+    /// * `viewport` - a [`Lens`] with a target of [`Viewport`] representing
+    ///   the zoomed section of the graph on both axes
+    /// * `max` - the max `x`, in `f32` seconds, of the envelope
+    pub fn new(cx: &mut Context, points: P, viewport: R, max: f32) -> Handle<Mseg<P, R>> {
+        let mut graph = Entity::null();
+        let overview_points = points.clone();
+        let handle = Self {
             points: points.clone(),
-            range: Default::default(),
+            viewport: viewport.clone(),
+            history: History::default(),
+            graph: Entity::null(),
             on_changing_point: None,
+            on_changing_curve: None,
+            on_changing_points: None,
+            on_restore_points: None,
             on_changing_range_start: None,
             on_changing_range_end: None,
             on_changing_range_both: None,
+            on_changing_range_y: None,
             on_remove_point: None,
             on_insert_point: None,
+            on_drag_start: None,
+            on_drag_end: None,
         }
         .build(cx, |cx| {
-            MsegGraph::new(cx, points, range.clone(), max)
-                .on_changing_point(|cx, index, point| {
-                    cx.emit(MsegInternalEvent::OnChangingPoint { index, point })
-                })
-                .on_remove_point(|cx, index| cx.emit(MsegInternalEvent::OnRemovePoint { index }))
-                .on_insert_point(|cx, index, point| {
-                    cx.emit(MsegInternalEvent::OnInsertPoint { index, point })
-                })
-                .class("graph");
-
-            Zoomer::new(cx, range.clone())
-                .on_changing_start(|cx, x| cx.emit(MsegInternalEvent::OnChangingRangeStart(x)))
-                .on_changing_end(|cx, x| cx.emit(MsegInternalEvent::OnChangingRangeEnd(x)))
-                .on_changing_both(|cx, start, end| {
-                    cx.emit(MsegInternalEvent::OnChangingRangeBoth { start, end })
+            // Line the ruler up with the graph, between the value axis and
+            // the vertical zoomer
+            MsegRuler::new(cx, viewport.clone().then(Viewport::x), max)
+                .class("ruler")
+                .left(Pixels(VALUE_AXIS_WIDTH))
+                .right(Pixels(ZOOMER_SIZE));
+
+            HStack::new(cx, |cx| {
+                MsegValueAxis::new(cx, viewport.clone().then(Viewport::y))
+                    .class("value-axis")
+                    .width(Pixels(VALUE_AXIS_WIDTH));
+                graph = MsegGraph::new(cx, points, viewport.clone(), max)
+                    .on_changing_point(|cx, index, point| {
+                        cx.emit(MsegInternalEvent::OnChangingPoint { index, point })
+                    })
+                    .on_remove_point(|cx, index| {
+                        cx.emit(MsegInternalEvent::OnRemovePoint { index })
+                    })
+                    .on_insert_point(|cx, index, point| {
+                        cx.emit(MsegInternalEvent::OnInsertPoint { index, point })
+                    })
+                    .on_changing_curve(|cx, index, curve| {
+                        cx.emit(MsegInternalEvent::OnChangingCurve { index, curve })
+                    })
+                    .on_changing_points(|cx, points| {
+                        cx.emit(MsegInternalEvent::OnChangingPoints(points))
+                    })
+                    .on_changing_viewport(|cx, viewport| {
+                        cx.emit(MsegInternalEvent::OnChangingViewport(viewport))
+                    })
+                    .on_drag_start(|cx| cx.emit(MsegInternalEvent::OnDragStart))
+                    .on_drag_end(|cx| cx.emit(MsegInternalEvent::OnDragEnd))
+                    .class("graph")
+                    .entity;
+
+                let y_range = viewport.clone().then(Viewport::y);
+                let (start_range, end_range) = (y_range.clone(), y_range.clone());
+                Zoomer::vertical(cx, y_range)
+                    .on_changing_start(move |cx, y| {
+                        let end = *end_range.get(cx).end();
+                        cx.emit(MsegInternalEvent::OnChangingRangeY(y..=end))
+                    })
+                    .on_changing_end(move |cx, y| {
+                        let start = *start_range.get(cx).start();
+                        cx.emit(MsegInternalEvent::OnChangingRangeY(start..=y))
+                    })
+                    .on_changing_both(|cx, start, end| {
+                        cx.emit(MsegInternalEvent::OnChangingRangeY(start..=end))
+                    });
+            })
+            .class("body");
+
+            // Show an overview of the whole envelope behind the zoomer
+            let x_range = viewport.clone().then(Viewport::x);
+            Zoomer::with_content(cx, x_range, move |cx, canvas, bounds| {
+                let color = cx.border_color().copied().unwrap_or_default();
+                overview_points.view(cx.data().unwrap(), |points| {
+                    let mut path = envelope_path(points.unwrap(), |point| {
+                        data_to_bounds_pos_range(bounds, point, Viewport::default(), max)
+                    });
+                    canvas.stroke_path(&mut path, &vg::Paint::color(color.into()));
                 });
+            })
+            .on_changing_start(|cx, x| cx.emit(MsegInternalEvent::OnChangingRangeStart(x)))
+            .on_changing_end(|cx, x| cx.emit(MsegInternalEvent::OnChangingRangeEnd(x)))
+            .on_changing_both(|cx, start, end| {
+                cx.emit(MsegInternalEvent::OnChangingRangeBoth { start, end })
+            });
+        });
+        modify_view(handle.cx, handle.entity, |mseg: &mut Self| mseg.graph = graph);
+        handle
+    }
+}
+
+/// Modifiers for configuring an [`Mseg`] after it has been built
+pub trait MsegModifiers {
+    /// Sets the grid drawn behind the envelope. Dragged points snap to the
+    /// grid unless alt is held.
+    fn grid(self, grid: MsegGrid) -> Self;
+
+    /// Keeps the grid in step with a grid in the model, for grids that change
+    /// while the editor is open, like one synced to the host's tempo
+    fn bind_grid<G>(self, grid: G) -> Self
+    where
+        G: Lens<Target = MsegGrid>;
+}
+
+impl<'a, P, R> MsegModifiers for Handle<'a, Mseg<P, R>>
+where
+    P: Lens<Target = CurvePoints>,
+    R: Lens<Target = Viewport>,
+{
+    fn grid(self, grid: MsegGrid) -> Self {
+        let mut graph = Entity::null();
+        modify_view(self.cx, self.entity, |mseg: &mut Mseg<P, R>| graph = mseg.graph);
+        modify_view(self.cx, graph, |graph: &mut MsegGraph<P, R>| graph.grid = grid);
+        self
+    }
+
+    fn bind_grid<G>(self, grid: G) -> Self
+    where
+        G: Lens<Target = MsegGrid>,
+    {
+        self.bind(grid, |handle, grid| {
+            let grid = grid.get(handle.cx);
+            handle.grid(grid);
         })
     }
 }
@@ -91,45 +240,112 @@ where
 impl<P, R> View for Mseg<P, R>
 where
     P: Lens<Target = CurvePoints>,
-    R: Lens<Target = RangeInclusive<f32>>,
+    R: Lens<Target = Viewport>,
 {
     fn element(&self) -> Option<&'static str> {
         Some("mseg")
     }
 
     fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
-        event.map(|ev: &MsegInternalEvent, _| match *ev {
+        event.map(|ev: &MsegInternalEvent, _| match ev {
             MsegInternalEvent::OnChangingRangeStart(x) => {
                 if let Some(callback) = &self.on_changing_range_start {
-                    (callback)(cx, x);
+                    (callback)(cx, *x);
                 }
             }
             MsegInternalEvent::OnChangingRangeEnd(x) => {
                 if let Some(callback) = &self.on_changing_range_end {
-                    (callback)(cx, x);
+                    (callback)(cx, *x);
                 }
             }
             MsegInternalEvent::OnChangingRangeBoth { start, end } => {
                 if let Some(callback) = &self.on_changing_range_both {
-                    (callback)(cx, start..=end);
+                    (callback)(cx, *start..=*end);
+                }
+            }
+            MsegInternalEvent::OnChangingRangeY(range) => {
+                if let Some(callback) = &self.on_changing_range_y {
+                    (callback)(cx, range.clone());
+                }
+            }
+            // Zooming on the graph can change either axis
+            MsegInternalEvent::OnChangingViewport(viewport) => {
+                let current = self.viewport.get(cx);
+                if viewport.x != current.x {
+                    if let Some(callback) = &self.on_changing_range_both {
+                        (callback)(cx, viewport.x.clone());
+                    }
+                }
+                if viewport.y != current.y {
+                    if let Some(callback) = &self.on_changing_range_y {
+                        (callback)(cx, viewport.y.clone());
+                    }
                 }
             }
             MsegInternalEvent::OnChangingPoint { index, point } => {
                 if let Some(callback) = &self.on_changing_point {
-                    (callback)(cx, index, point);
+                    self.history.record_with(|| self.points.get(cx));
+                    (callback)(cx, *index, *point);
                 }
             }
             MsegInternalEvent::OnRemovePoint { index } => {
                 // Delete the point if not the first or last in the vector
-                if index != 0 && index != self.points.get(cx).len() - 1 {
+                if *index != 0 && *index != self.points.get(cx).len() - 1 {
                     if let Some(callback) = &self.on_remove_point {
-                        (callback)(cx, index);
+                        self.history.record_with(|| self.points.get(cx));
+                        (callback)(cx, *index);
                     }
                 }
             }
             MsegInternalEvent::OnInsertPoint { index, point } => {
                 if let Some(callback) = &self.on_insert_point {
-                    (callback)(cx, index, point);
+                    self.history.record_with(|| self.points.get(cx));
+                    (callback)(cx, *index, *point);
+                }
+            }
+            MsegInternalEvent::OnChangingCurve { index, curve } => {
+                if let Some(callback) = &self.on_changing_curve {
+                    self.history.record_with(|| self.points.get(cx));
+                    (callback)(cx, *index, *curve);
+                }
+            }
+            MsegInternalEvent::OnChangingPoints(points) => {
+                if let Some(callback) = &self.on_changing_points {
+                    self.history.record_with(|| self.points.get(cx));
+                    (callback)(cx, points.clone());
+                }
+            }
+            // Coalesce drags into a single undo step
+            MsegInternalEvent::OnDragStart => {
+                self.history.begin_gesture(self.points.get(cx));
+                if let Some(callback) = &self.on_drag_start {
+                    (callback)(cx);
+                }
+            }
+            MsegInternalEvent::OnDragEnd => {
+                self.history.end_gesture();
+                if let Some(callback) = &self.on_drag_end {
+                    (callback)(cx);
+                }
+            }
+        });
+
+        // Undo and redo shortcuts, received while the graph is focused
+        event.map(|ev: &WindowEvent, meta| {
+            if let WindowEvent::KeyDown(code, _) = *ev {
+                if let (Some(action), Some(callback)) = (
+                    HistoryAction::from_key(code, *cx.modifiers),
+                    &self.on_restore_points,
+                ) {
+                    let current = self.points.get(cx);
+                    let restored = match action {
+                        HistoryAction::Undo => self.history.undo(current),
+                        HistoryAction::Redo => self.history.redo(current),
+                    };
+                    if let Some(points) = restored {
+                        (callback)(cx, points);
+                    }
+                    meta.consume();
                 }
             }
         });
@@ -0,0 +1,200 @@
+//! Time and value labels for the MSEG
+
+use std::ops::RangeInclusive;
+use vizia::prelude::*;
+use vizia::vg;
+
+/// The most labels to show along the time ruler
+const MAX_TIME_TICKS: usize = 8;
+/// The most labels to show along the value axis
+const MAX_VALUE_TICKS: usize = 4;
+/// The length in pixels of tick marks
+const TICK_LENGTH: f32 = 4f32;
+/// The width in pixels of the value axis beside the graph
+pub(crate) const VALUE_AXIS_WIDTH: f32 = 36f32;
+
+/// A ruler of time labels that follows the zoom of the graph
+pub(crate) struct MsegRuler<R>
+where
+    R: Lens<Target = RangeInclusive<f32>>,
+{
+    range: R,
+    max: f32,
+}
+
+impl<R> MsegRuler<R>
+where
+    R: Lens<Target = RangeInclusive<f32>>,
+{
+    /// Create a new `MsegRuler` over the same `range` and `max` as an
+    /// [`MsegGraph`](super::graph::MsegGraph)
+    pub fn new(cx: &mut Context, range: R, max: f32) -> Handle<Self> {
+        Self {
+            range: range.clone(),
+            max,
+        }
+        .build(cx, |cx| {
+            Binding::new(cx, range, move |cx, range| {
+                let range = range.get(cx);
+                let (start, end) = (range.start() * max, range.end() * max);
+                let step = tick_step(end - start, MAX_TIME_TICKS);
+                for x in ticks(start, end, step) {
+                    Label::new(cx, &format_tick(x, step, "s"))
+                        .position_type(PositionType::SelfDirected)
+                        .left(Percentage((x - start) / (end - start) * 100f32))
+                        .class("tick");
+                }
+            });
+        })
+    }
+}
+
+impl<R> View for MsegRuler<R>
+where
+    R: Lens<Target = RangeInclusive<f32>>,
+{
+    fn element(&self) -> Option<&'static str> {
+        Some("ruler")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        let color = cx.border_color().copied().unwrap_or_default();
+        let range = self
+            .range
+            .view(cx.data().unwrap(), |range| range.unwrap().clone());
+        let (start, end) = (range.start() * self.max, range.end() * self.max);
+
+        let mut path = vg::Path::new();
+        for x in ticks(start, end, tick_step(end - start, MAX_TIME_TICKS)) {
+            let x = bounds.left() + (x - start) / (end - start) * bounds.w;
+            path.move_to(x, bounds.bottom() - TICK_LENGTH);
+            path.line_to(x, bounds.bottom());
+        }
+        canvas.stroke_path(
+            &mut path,
+            &vg::Paint::color(color.into()).with_line_width(1f32),
+        );
+    }
+}
+
+/// Value labels for the side of the graph that follow its vertical zoom
+pub(crate) struct MsegValueAxis<R>
+where
+    R: Lens<Target = RangeInclusive<f32>>,
+{
+    range: R,
+}
+
+impl<R> MsegValueAxis<R>
+where
+    R: Lens<Target = RangeInclusive<f32>>,
+{
+    /// Create a new `MsegValueAxis` over the vertical `range` of an
+    /// [`MsegGraph`](super::graph::MsegGraph)
+    pub fn new(cx: &mut Context, range: R) -> Handle<Self> {
+        Self {
+            range: range.clone(),
+        }
+        .build(cx, |cx| {
+            Binding::new(cx, range, |cx, range| {
+                let range = range.get(cx);
+                let (start, end) = (*range.start(), *range.end());
+                let step = tick_step(end - start, MAX_VALUE_TICKS);
+                for y in ticks(start, end, step) {
+                    Label::new(cx, &format_tick(y, step, ""))
+                        .position_type(PositionType::SelfDirected)
+                        .top(Percentage((end - y) / (end - start) * 100f32))
+                        .class("tick");
+                }
+            });
+        })
+    }
+}
+
+impl<R> View for MsegValueAxis<R>
+where
+    R: Lens<Target = RangeInclusive<f32>>,
+{
+    fn element(&self) -> Option<&'static str> {
+        Some("value-axis")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        let color = cx.border_color().copied().unwrap_or_default();
+        let range = self
+            .range
+            .view(cx.data().unwrap(), |range| range.unwrap().clone());
+        let (start, end) = (*range.start(), *range.end());
+
+        let mut path = vg::Path::new();
+        for y in ticks(start, end, tick_step(end - start, MAX_VALUE_TICKS)) {
+            let y = bounds.bottom() - (y - start) / (end - start) * bounds.h;
+            path.move_to(bounds.right() - TICK_LENGTH, y);
+            path.line_to(bounds.right(), y);
+        }
+        canvas.stroke_path(
+            &mut path,
+            &vg::Paint::color(color.into()).with_line_width(1f32),
+        );
+    }
+}
+
+/// Gets the smallest "nice" step (1, 2 or 5 times a power of ten) that
+/// divides `span` into at most `max_ticks` steps
+pub(crate) fn tick_step(span: f32, max_ticks: usize) -> f32 {
+    let raw = span / max_ticks.max(1) as f32;
+    if !raw.is_finite() || raw <= 0f32 {
+        return 1f32;
+    }
+    let magnitude = 10f32.powf(raw.log10().floor());
+    [1f32, 2f32, 5f32, 10f32]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|step| *step >= raw * (1f32 - f32::EPSILON))
+        .unwrap_or(10f32 * magnitude)
+}
+
+/// Every multiple of `step` from `start..=end`
+pub(crate) fn ticks(start: f32, end: f32, step: f32) -> impl Iterator<Item = f32> {
+    // Allow for a little floating point error at the edges
+    let first = (start / step - 1e-4).ceil() as i64;
+    let last = (end / step + 1e-4).floor() as i64;
+    (first..=last).map(move |i| i as f32 * step)
+}
+
+/// Formats a tick with just enough decimals to tell apart ticks `step` apart
+pub(crate) fn format_tick(value: f32, step: f32, unit: &str) -> String {
+    let decimals = (-step.log10().floor()).max(0f32) as usize;
+    format!("{:.*}{}", decimals, value, unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn gets_nice_steps() {
+        assert_approx_eq!(tick_step(8f32, 8), 1f32);
+        assert_approx_eq!(tick_step(8f32, 4), 2f32);
+        assert_approx_eq!(tick_step(3f32, 8), 0.5);
+        assert_approx_eq!(tick_step(0.8, 8), 0.1);
+        assert_approx_eq!(tick_step(1f32, 4), 0.5);
+        assert_approx_eq!(tick_step(60f32, 4), 20f32);
+    }
+
+    #[test]
+    fn gets_ticks() {
+        let ticks: Vec<f32> = ticks(0.3, 2f32, 0.5).collect();
+        assert_eq!(ticks, vec![0.5, 1.0, 1.5, 2.0]);
+    }
+
+    #[test]
+    fn formats_ticks() {
+        assert_eq!(format_tick(2f32, 1f32, "s"), "2s");
+        assert_eq!(format_tick(1.5, 0.5, "s"), "1.5s");
+        assert_eq!(format_tick(0.25, 0.05, ""), "0.25");
+    }
+}
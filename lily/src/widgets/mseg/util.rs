@@ -1,4 +1,4 @@
-use crate::util::RangeExt;
+use crate::util::{CurvePoints, RangeExt, Viewport};
 use glam::Vec2;
 use std::ops::RangeInclusive;
 use vizia::cache::BoundingBox;
@@ -9,26 +9,26 @@ use vizia::prelude::*;
 pub fn ui_to_data_pos_range(
     cx: &EventContext,
     ui_point: &Vec2,
-    range_data: impl Lens<Target = RangeInclusive<f32>>,
+    viewport: impl Lens<Target = Viewport>,
     max_data: f32,
 ) -> Vec2 {
     _ui_to_data_pos_range(
         cx.cache.get_bounds(cx.current()),
         *ui_point,
-        range_data.get(cx),
+        viewport.get(cx),
         max_data,
     )
 }
 pub fn data_to_ui_pos_range(
     cx: &EventContext,
     point: Vec2,
-    range_data: impl Lens<Target = RangeInclusive<f32>>,
+    viewport: impl Lens<Target = Viewport>,
     max: f32,
 ) -> Vec2 {
     data_to_bounds_pos_range(
         cx.cache.get_bounds(cx.current()),
         point,
-        range_data.get(cx),
+        viewport.get(cx),
         max,
     )
 }
@@ -36,7 +36,7 @@ pub fn data_to_ui_pos_range(
 fn _ui_to_data_pos_range(
     bounds: BoundingBox,
     ui_point: Vec2,
-    range: RangeInclusive<f32>,
+    viewport: Viewport,
     max: f32,
 ) -> Vec2 {
     let (width, height) = (bounds.w, bounds.h);
@@ -47,42 +47,156 @@ fn _ui_to_data_pos_range(
     // Convert to relative point
     ui_point -= offset;
     // Scale points to fit within `(x,y) = ([0..=max], [0..=1])`
-    let y = (height - ui_point.y) / height;
-    let offset_data = range.start() * max;
-    let scale = range.width() * max;
-    let x = ((ui_point.x / width) * scale) + offset_data;
-    Vec2::new(x, y)
+    let (x, y) = viewport.unmap(ui_point.x / width, (height - ui_point.y) / height);
+    Vec2::new(x * max, y)
 }
 
 pub fn data_to_bounds_pos_range(
     bounds: BoundingBox,
     point: Vec2,
-    range: RangeInclusive<f32>,
+    viewport: Viewport,
     max: f32,
 ) -> Vec2 {
     let (width, height) = (bounds.w, bounds.h);
-    // y value is a simple scale
-    let y = height - (point.y * height);
-    // x value requires us to calculate our zoomed position TODO: Zoom too
-
-    // Calculate the x-offset determined by the current view zoom window This
-    // value shifts points to the left and right We calculate the offset by
-    // getting the view's starting x value, which is normalized. We then see how
-    // much time that offsets by multiplying that normalized value times the
-    // maximum X of our MSEG.
-    let offset = range.start() * max;
-    // Calculate the x-zoom scale to apply to points
-    let scale = 1f32 / (range.width() * max);
-    let x = ((point.x - offset) * scale) * width;
-    let relative = Vec2::new(x, y);
+    // Normalize the point and see where it lands within the zoomed window.
+    // Points outside of the window map outside of the bounds.
+    let (x, y) = viewport.map(point.x / max, point.y);
+    // y values start from the top
+    let relative = Vec2::new(x * width, height - (y * height));
     // adjust to be absolute by adding the container coords
     let offset = { Vec2::new(bounds.x, bounds.y) };
     relative + offset
 }
 
+/// Moves a group of points by `delta` from their positions in `origins`
+/// (pairs of index and starting position). The delta is constrained so that
+/// the group keeps at least `min_distance` from the points around it and stays
+/// within `(0,0)..=(max,1)`. Like single points, the first point never moves
+/// and the last point stays at a `y` of `0`.
+pub fn constrain_group_move(
+    points: &CurvePoints,
+    origins: &[(usize, Vec2)],
+    delta: Vec2,
+    max: f32,
+    min_distance: f32,
+) -> Vec<(usize, Vec2)> {
+    let last = points.len().saturating_sub(1);
+    let origins: Vec<(usize, Vec2)> = origins
+        .iter()
+        .copied()
+        .filter(|(i, _)| *i != 0 && *i <= last)
+        .collect();
+    let is_moving = |index: usize| origins.iter().any(|(i, _)| *i == index);
+
+    let (mut lower, mut upper) = (Vec2::splat(f32::MIN), Vec2::splat(f32::MAX));
+    for (i, origin) in &origins {
+        // Only the edges of each run of moving points are limited by their
+        // neighbours, since points within a run move together
+        if !is_moving(i - 1) {
+            lower.x = lower.x.max(points[i - 1].x + min_distance - origin.x);
+        }
+        if !is_moving(i + 1) {
+            let right = points.get(i + 1).map(|p| p.x).unwrap_or(max);
+            upper.x = upper.x.min(right - min_distance - origin.x);
+        }
+        if *i != last {
+            lower.y = lower.y.max(-origin.y);
+            upper.y = upper.y.min(1f32 - origin.y);
+        }
+    }
+    let delta = delta.clamp(lower, upper.max(lower));
+
+    origins
+        .iter()
+        .map(|(i, origin)| {
+            let mut moved = *origin + delta;
+            if *i == last {
+                moved.y = 0f32;
+            }
+            (*i, moved)
+        })
+        .collect()
+}
+
+/// Zooms `range` by `factor` around `anchor`, a normalized position within the
+/// range that stays in place. A `factor` below `1` zooms in. The result keeps a
+/// width of at least `min_width` and stays within `0..=1`.
+pub fn zoom_range(
+    range: &RangeInclusive<f32>,
+    anchor: f32,
+    factor: f32,
+    min_width: f32,
+) -> RangeInclusive<f32> {
+    let width = (range.width() * factor).clamp(min_width.min(1f32), 1f32);
+    let pivot = range.start() + range.width() * anchor;
+    let start = pivot - width * anchor;
+    pan_range(&(start..=start + width), 0f32)
+}
+
+/// Moves `range` by `delta` while keeping its width and staying within `0..=1`
+pub fn pan_range(range: &RangeInclusive<f32>, delta: f32) -> RangeInclusive<f32> {
+    let width = range.width().min(1f32);
+    let start = (range.start() + delta).clamp(0f32, 1f32 - width);
+    start..=start + width
+}
+
+/// Finds the point on the envelope closest to `cursor` on screen, within
+/// `radius` pixels. Segments are sampled about every `step` pixels, as they
+/// are drawn, and mapped to the UI with `to_ui`. Returns the index of the
+/// segment and the data position of the closest point.
+pub fn nearest_on_curve(
+    points: &CurvePoints,
+    cursor: Vec2,
+    radius: f32,
+    step: f32,
+    to_ui: impl Fn(Vec2) -> Vec2,
+) -> Option<(usize, Vec2)> {
+    let (mut nearest, mut nearest_distance) = (None, radius);
+    for (index, pair) in points.windows(2).enumerate() {
+        let (left, right) = (pair[0], pair[1]);
+        let (ui_left, ui_right) = (
+            to_ui(Vec2::new(left.x, left.y)),
+            to_ui(Vec2::new(right.x, right.y)),
+        );
+        // Skip segments that are too far to the side of the cursor
+        if cursor.x < ui_left.x.min(ui_right.x) - radius
+            || cursor.x > ui_left.x.max(ui_right.x) + radius
+        {
+            continue;
+        }
+        let steps = ((ui_right.x - ui_left.x) / step).ceil().max(1f32) as usize;
+        let sample = |i: usize| {
+            let x = left.x + (right.x - left.x) * (i as f32 / steps as f32);
+            Vec2::new(x, points.segment_value(index, x))
+        };
+        let mut start = sample(0);
+        let mut ui_start = to_ui(start);
+        for i in 1..=steps {
+            let end = sample(i);
+            let ui_end = to_ui(end);
+            // The closest point along this straight piece of the curve
+            let piece = ui_end - ui_start;
+            let t = match piece.length_squared() > 0f32 {
+                true => ((cursor - ui_start).dot(piece) / piece.length_squared()).clamp(0f32, 1f32),
+                false => 0f32,
+            };
+            let distance = (ui_start + piece * t).distance(cursor);
+            if distance <= nearest_distance {
+                let x = start.x + (end.x - start.x) * t;
+                nearest = Some((index, Vec2::new(x, points.segment_value(index, x))));
+                nearest_distance = distance;
+            }
+            start = end;
+            ui_start = ui_end;
+        }
+    }
+    nearest
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::CurvePoint;
     use assert_approx_eq::assert_approx_eq;
 
     fn rect() -> BoundingBox {
@@ -97,16 +211,157 @@ mod tests {
     #[test]
     fn gets_ui_point_from_data() {
         let rect = rect();
-        let ui_point = data_to_bounds_pos_range(rect, Vec2::new(0.6, 0.5), 0.2..=0.4, 2f32);
+        let viewport = Viewport::new(0.2..=0.4, 0f32..=1f32);
+        let ui_point = data_to_bounds_pos_range(rect, Vec2::new(0.6, 0.5), viewport, 2f32);
         assert_eq!(ui_point.x.round(), 110f32);
         assert_eq!(ui_point.y.round(), 60f32);
     }
 
+    #[test]
+    fn zooms_value_axis() {
+        let rect = rect();
+        let viewport = Viewport::new(0f32..=1f32, 0f32..=0.25);
+        let ui_point =
+            data_to_bounds_pos_range(rect, Vec2::new(1f32, 0.125), viewport.clone(), 2f32);
+        assert_eq!(ui_point.x.round(), 110f32);
+        assert_eq!(ui_point.y.round(), 60f32);
+        let data_point = _ui_to_data_pos_range(rect, ui_point, viewport, 2f32);
+        assert_approx_eq!(data_point.x, 1f32);
+        assert_approx_eq!(data_point.y, 0.125);
+    }
+
+    #[test]
+    fn finds_steep_segments() {
+        let rect = rect();
+        let viewport = Viewport::new(0f32..=1f32, 0f32..=1f32);
+        let points = CurvePoints::new(
+            [(0f32, 0f32), (1f32, 0f32), (1.05, 1f32), (4f32, 1f32)]
+                .into_iter()
+                .map(CurvePoint::from)
+                .collect(),
+        );
+        let to_ui = |point| data_to_bounds_pos_range(rect, point, viewport.clone(), 4f32);
+        // Beside the middle of the near-vertical segment, far above or below
+        // the curve at the cursor's time
+        let (index, point) =
+            nearest_on_curve(&points, Vec2::new(66f32, 60f32), 16f32, 4f32, to_ui).unwrap();
+        assert_eq!(index, 1);
+        assert_approx_eq!(point.y, 0.5, 0.1);
+        assert_eq!(
+            nearest_on_curve(&points, Vec2::new(120f32, 60f32), 16f32, 4f32, to_ui),
+            None
+        );
+    }
+
     #[test]
     fn gets_data_point_from_ui() {
         let rect = rect();
-        let data_point = _ui_to_data_pos_range(rect, Vec2::new(110f32, 60f32), 0.2..=0.4, 2f32);
+        let viewport = Viewport::new(0.2..=0.4, 0f32..=1f32);
+        let data_point = _ui_to_data_pos_range(rect, Vec2::new(110f32, 60f32), viewport, 2f32);
         assert_approx_eq!(data_point.x, 0.6);
         assert_approx_eq!(data_point.y, 0.5);
     }
+
+    fn points() -> CurvePoints {
+        CurvePoints::new(
+            [(0f32, 0f32), (1f32, 0.5f32), (2f32, 0.8f32), (3f32, 0.2f32), (4f32, 0f32)]
+                .into_iter()
+                .map(CurvePoint::from)
+                .collect(),
+        )
+    }
+
+    fn origins(points: &CurvePoints, indices: &[usize]) -> Vec<(usize, Vec2)> {
+        indices
+            .iter()
+            .map(|i| (*i, Vec2::new(points[*i].x, points[*i].y)))
+            .collect()
+    }
+
+    #[test]
+    fn moves_group() {
+        let points = points();
+        let moved = constrain_group_move(
+            &points,
+            &origins(&points, &[1, 2]),
+            Vec2::new(0.5, 0.1),
+            8f32,
+            0.01,
+        );
+        assert_eq!(moved.len(), 2);
+        assert_approx_eq!(moved[0].1.x, 1.5);
+        assert_approx_eq!(moved[0].1.y, 0.6);
+        assert_approx_eq!(moved[1].1.x, 2.5);
+        assert_approx_eq!(moved[1].1.y, 0.9);
+    }
+
+    #[test]
+    fn group_stays_between_neighbours() {
+        let points = points();
+        let origins = origins(&points, &[1, 2]);
+        let moved = constrain_group_move(&points, &origins, Vec2::new(5f32, 0f32), 8f32, 0.01);
+        assert_approx_eq!(moved[1].1.x, 2.99);
+        assert_approx_eq!(moved[0].1.x, 1.99);
+        let moved = constrain_group_move(&points, &origins, Vec2::new(-5f32, 0f32), 8f32, 0.01);
+        assert_approx_eq!(moved[0].1.x, 0.01);
+    }
+
+    #[test]
+    fn group_stays_in_value_range() {
+        let points = points();
+        let origins = origins(&points, &[1, 2, 3]);
+        let moved = constrain_group_move(&points, &origins, Vec2::new(0f32, 1f32), 8f32, 0.01);
+        // The highest point limits the whole group
+        assert_approx_eq!(moved[1].1.y, 1f32);
+        assert_approx_eq!(moved[0].1.y, 0.7);
+    }
+
+    #[test]
+    fn group_keeps_pinned_points() {
+        let points = points();
+        let origins = origins(&points, &[0, 3, 4]);
+        let moved = constrain_group_move(&points, &origins, Vec2::new(10f32, 0.5), 8f32, 0.01);
+        // The first point is never moved
+        assert_eq!(moved.len(), 2);
+        // The last point is limited by the maximum and stays at zero
+        assert_approx_eq!(moved[1].1.x, 7.99);
+        assert_approx_eq!(moved[1].1.y, 0f32);
+        assert_approx_eq!(moved[0].1.y, 0.7);
+    }
+
+    #[test]
+    fn zooms_around_anchor() {
+        let range = zoom_range(&(0.2..=0.6), 0.5, 0.5, 0.1);
+        assert_approx_eq!(*range.start(), 0.3);
+        assert_approx_eq!(*range.end(), 0.5);
+        // The anchor stays under the cursor
+        let range = zoom_range(&(0f32..=1f32), 0.25, 0.5, 0.1);
+        assert_approx_eq!(*range.start(), 0.125);
+        assert_approx_eq!(*range.end(), 0.625);
+    }
+
+    #[test]
+    fn zoom_stays_in_bounds() {
+        let range = zoom_range(&(0.1..=0.9), 0.5, 2f32, 0.1);
+        assert_approx_eq!(*range.start(), 0f32);
+        assert_approx_eq!(*range.end(), 1f32);
+        let range = zoom_range(&(0.8..=0.9), 0f32, 3f32, 0.1);
+        assert_approx_eq!(*range.start(), 0.7);
+        assert_approx_eq!(*range.end(), 1f32);
+        let range = zoom_range(&(0.4..=0.6), 0.5, 0.1, 0.1);
+        assert_approx_eq!(range.width(), 0.1);
+    }
+
+    #[test]
+    fn pans_range() {
+        let range = pan_range(&(0.2..=0.4), 0.1);
+        assert_approx_eq!(*range.start(), 0.3);
+        assert_approx_eq!(*range.end(), 0.5);
+        let range = pan_range(&(0.2..=0.4), -0.5);
+        assert_approx_eq!(*range.start(), 0f32);
+        assert_approx_eq!(*range.end(), 0.2);
+        let range = pan_range(&(0.2..=0.4), 1f32);
+        assert_approx_eq!(*range.start(), 0.8);
+        assert_approx_eq!(*range.end(), 1f32);
+    }
 }
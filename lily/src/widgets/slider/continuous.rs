@@ -1,7 +1,10 @@
-use crate::util::{BoundingBoxExt, RangeExt};
+use crate::util::{
+    end_drag, modify_view, reset_to_default, start_drag, DragCallback, Parameter, Taper,
+};
+use crate::widgets::entry::{build_entry, EntryEvent};
 use glam::Vec2;
 use lily_derive::Handle;
-use std::{marker::PhantomData, ops::RangeInclusive};
+use std::ops::RangeInclusive;
 // use vizia::context::Context;
 use vizia::prelude::*;
 use vizia::vg::{Paint, Path};
@@ -9,19 +12,42 @@ use vizia::vg::{Paint, Path};
 const VERTICAL: bool = true;
 const HORIZONTAL: bool = false;
 
+/// The default distance in pixels to drag to sweep the whole range in
+/// relative mode
+const DEFAULT_SENSITIVITY: f32 = 200f32;
+/// The default scale of value changes while holding shift
+const DEFAULT_FINE_SCALAR: f32 = 0.1;
+
+/// A slider for a continuous value. Drag it to change the value, or press
+/// enter or double-click it to type in an exact value.
 #[derive(Handle)]
 pub struct DragSlider<L>
 where
     L: Lens<Target = f32>,
 {
-    value: PhantomData<L>,
-    range: PhantomData<RangeInclusive<f32>>,
+    value: L,
+    param: Parameter,
+    /// The entity of the bar, for passing on modifiers
+    bar: Entity,
     #[callback(f32)]
     on_changing: Option<Box<dyn Fn(&mut EventContext, f32)>>,
+    /// Called with the default value when the slider is reset. Falls back to
+    /// `on_changing` if not set.
+    #[callback(f32)]
+    on_reset: Option<Box<dyn Fn(&mut EventContext, f32)>>,
+    #[callback]
+    on_drag_start: DragCallback,
+    #[callback]
+    on_drag_end: DragCallback,
 }
 
 pub enum InternalEvent {
     Changing(f32),
+    Reset(f32),
+    DragStart,
+    DragEnd,
+    /// Open the text entry for typing in a value
+    StartEntry,
 }
 
 impl<L> DragSlider<L>
@@ -38,18 +64,114 @@ where
     /// * `range` - the arbitrary range of this slider. In most cases, you'll
     ///   want `0f32..=1f32` or `-1f32..=1f32` for a centered slider.
     pub fn new(cx: &mut Context, value: L, range: RangeInclusive<f32>) -> Handle<Self> {
-        Self {
-            value: PhantomData::default(),
+        Self::with_param(cx, value, Parameter::new(range))
+    }
+
+    /// Create a new `DragSlider` whose mapping, default value and text entry
+    /// follow a [`Parameter`]
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - Vizia `Context`
+    /// * `value` - a `vizia::Lens` specifying the plain value of this slider
+    /// * `param` - the definition of the value
+    pub fn with_param(cx: &mut Context, value: L, param: Parameter) -> Handle<Self> {
+        let mut bar = Entity::null();
+        let handle = Self {
+            value: value.clone(),
             on_changing: None,
-            range: PhantomData::default(),
+            on_reset: None,
+            on_drag_start: None,
+            on_drag_end: None,
+            param: param.clone(),
+            bar: Entity::null(),
         }
         .build(cx, |cx| {
             // Foreground interactive slider
-            SliderBar::new(cx, value.clone(), range.clone())
+            bar = SliderBar::new(cx, value.clone(), param.clone())
                 .class("bar")
-                .on_changing(|cx, value| cx.emit(InternalEvent::Changing(value)));
-        })
+                .on_changing(|cx, value| cx.emit(InternalEvent::Changing(value)))
+                .on_reset(|cx, value| cx.emit(InternalEvent::Reset(value)))
+                .on_drag_start(|cx| cx.emit(InternalEvent::DragStart))
+                .on_drag_end(|cx| cx.emit(InternalEvent::DragEnd))
+                .entity;
+            build_entry(cx);
+        });
+        modify_view(handle.cx, handle.entity, |slider: &mut Self| slider.bar = bar);
+        handle
+    }
+}
+
+/// Modifiers for configuring how a [`DragSlider`] responds to dragging
+pub trait DragSliderModifiers {
+    /// Sets whether dragging moves the value relative to where it was
+    /// pressed at the speed set by [`sensitivity`](Self::sensitivity),
+    /// rather than following the cursor along the slider
+    fn relative(self, relative: bool) -> Self;
+    /// Sets the distance in pixels to drag to sweep the whole range in
+    /// relative mode
+    fn sensitivity(self, pixels: f32) -> Self;
+    /// Sets how much slower the value changes while holding shift
+    fn fine_scalar(self, scalar: f32) -> Self;
+    /// Sets the value to reset to when double-clicking or ctrl/cmd-clicking
+    /// the slider, which is also marked on the bar. Double-clicking then no
+    /// longer opens the text entry, which is still opened by pressing enter.
+    fn default_value(self, value: f32) -> Self;
+    /// Sets how values are spread along the slider, so that dragging follows
+    /// a logarithmic frequency or a decibel gain
+    fn taper(self, taper: Taper) -> Self;
+}
+
+impl<'a, L> DragSliderModifiers for Handle<'a, DragSlider<L>>
+where
+    L: Lens<Target = f32>,
+{
+    fn relative(self, relative: bool) -> Self {
+        modify_bar::<L, _>(self, |bar| bar.relative = relative)
+    }
+
+    fn sensitivity(self, pixels: f32) -> Self {
+        modify_bar::<L, _>(self, |bar| bar.sensitivity = pixels)
     }
+
+    fn fine_scalar(self, scalar: f32) -> Self {
+        modify_bar::<L, _>(self, |bar| bar.fine_scalar = scalar)
+    }
+
+    fn default_value(self, value: f32) -> Self {
+        modify_param(self, |param| param.with_default(value))
+    }
+
+    fn taper(self, taper: Taper) -> Self {
+        modify_param(self, |param| param.with_taper(taper))
+    }
+}
+
+/// Modifies the [`Parameter`] of a [`DragSlider`] and of its [`SliderBar`], so
+/// that text entry and dragging keep using the same mapping
+fn modify_param<L, F>(handle: Handle<DragSlider<L>>, f: F) -> Handle<DragSlider<L>>
+where
+    L: Lens<Target = f32>,
+    F: Fn(Parameter) -> Parameter,
+{
+    modify_view(handle.cx, handle.entity, |slider: &mut DragSlider<L>| {
+        slider.param = f(slider.param.clone())
+    });
+    modify_bar::<L, _>(handle, |bar| bar.param = f(bar.param.clone()))
+}
+
+/// Modifies the [`SliderBar`] of a [`DragSlider`]
+fn modify_bar<L, F>(handle: Handle<DragSlider<L>>, f: F) -> Handle<DragSlider<L>>
+where
+    L: Lens<Target = f32>,
+    F: FnOnce(&mut SliderBar<L>),
+{
+    let mut bar = Entity::null();
+    modify_view(handle.cx, handle.entity, |slider: &mut DragSlider<L>| {
+        bar = slider.bar
+    });
+    modify_view(handle.cx, bar, f);
+    handle
 }
 
 impl<L> View for DragSlider<L>
@@ -67,6 +189,34 @@ where
                     (callback)(cx, value);
                 }
             }
+            InternalEvent::Reset(value) => {
+                if let Some(callback) = self.on_reset.as_ref().or(self.on_changing.as_ref()) {
+                    (callback)(cx, value);
+                }
+            }
+            InternalEvent::DragStart => {
+                if let Some(callback) = &self.on_drag_start {
+                    (callback)(cx);
+                }
+            }
+            InternalEvent::DragEnd => {
+                if let Some(callback) = &self.on_drag_end {
+                    (callback)(cx);
+                }
+            }
+            InternalEvent::StartEntry => {
+                cx.emit(EntryEvent::Open(self.param.format(self.value.get(cx))));
+            }
+        });
+        // Ignore typed values that aren't numbers
+        event.map(|ev: &EntryEvent, _| {
+            if let EntryEvent::Submit(text) = ev {
+                if let Some(value) = self.param.parse(text) {
+                    if let Some(callback) = &self.on_changing {
+                        (callback)(cx, value);
+                    }
+                }
+            }
         });
     }
 }
@@ -76,14 +226,42 @@ where
     L: Lens<Target = f32>,
 {
     value: L,
-    range: RangeInclusive<f32>,
+    param: Parameter,
     hover: bool,
     active: bool,
-    /// The offset of the cursor to the handle, set when clicking. This ensures
-    /// that values don't skip when first dragging to to cursor position
-    offset: f32,
+    /// Whether dragging moves the value at `sensitivity` rather than with the
+    /// cursor
+    relative: bool,
+    /// The distance in pixels to drag to sweep the whole range in relative
+    /// mode
+    sensitivity: f32,
+    /// How much slower the value changes while holding shift
+    fine_scalar: f32,
+    /// The last cursor position while dragging
+    drag_position: Vec2,
+    /// The normalized value while dragging. Moving it by the change in cursor
+    /// position, rather than setting it from the cursor position, ensures that
+    /// values don't skip when first dragging or when switching to and from
+    /// fine mode.
+    drag_value: f32,
     #[callback(f32)]
     on_changing: Option<Box<dyn Fn(&mut EventContext, f32)>>,
+    #[callback(f32)]
+    on_reset: Option<Box<dyn Fn(&mut EventContext, f32)>>,
+    #[callback]
+    on_drag_start: DragCallback,
+    #[callback]
+    on_drag_end: DragCallback,
+}
+
+impl<L> SliderBar<L>
+where
+    L: Lens<Target = f32>,
+{
+    /// Calls `on_reset` with the default value, if there is one
+    fn reset(&self, cx: &mut EventContext) {
+        reset_to_default(cx, self.param.default, self.on_reset.as_deref());
+    }
 }
 
 impl<L> View for SliderBar<L>
@@ -98,70 +276,66 @@ where
             }
             WindowEvent::MouseDown(button) => {
                 if button == MouseButton::Left {
-                    cx.capture();
-                    self.active = true;
-                    // set the offset
-                    let rect = cx.cache.get_bounds(cx.current());
-                    let mouse_pos: Vec2 = (cx.mouse.cursorx, cx.mouse.cursory).into();
-                    // get the difference between the mapped mouse pos and
-                    // the current value
-                    let mouse_mapped = rect.map_ui_point(mouse_pos, true);
-                    self.offset = self.value.get(cx)
-                        - match rect.h > rect.w {
-                            VERTICAL => mouse_mapped.y,
-                            HORIZONTAL => mouse_mapped.x,
-                        };
+                    if cx.modifiers.contains(Modifiers::CTRL)
+                        || cx.modifiers.contains(Modifiers::LOGO)
+                    {
+                        self.reset(cx);
+                        return;
+                    }
+                    // Receive keyboard events for opening the text entry
+                    cx.focus();
+                    self.drag_position = Vec2::new(cx.mouse.cursorx, cx.mouse.cursory);
+                    self.drag_value = self.param.normalize(self.value.get(cx));
+                    start_drag(cx, &mut self.active, &self.on_drag_start);
+                }
+            }
+            WindowEvent::MouseDoubleClick(button) => {
+                if button == MouseButton::Left {
+                    // Stop the drag started by the first click
+                    end_drag(cx, &mut self.active, &self.on_drag_end);
+                    match self.param.default {
+                        Some(_) => self.reset(cx),
+                        None => cx.emit(InternalEvent::StartEntry),
+                    }
                 }
             }
+            WindowEvent::KeyDown(Code::Enter, _) => cx.emit(InternalEvent::StartEntry),
             WindowEvent::MouseUp(button) => {
                 if button == MouseButton::Left {
-                    cx.release();
-                    self.active = false;
+                    end_drag(cx, &mut self.active, &self.on_drag_end);
                 }
-                // reset offset
-                self.offset = 0f32;
             }
-            // TODO: figure out a way to not rely on the map_ui_point and
-            // instead just have some sort of scalar
             WindowEvent::MouseMove(x, y) => {
                 if self.active {
+                    // determine whether we are reacting to a vertical or
+                    // horizontal slider
+                    let rect = cx.cache.get_bounds(cx.current());
+                    let orientation = rect.h > rect.w;
+                    let delta = Vec2::new(x, y) - self.drag_position;
+                    self.drag_position = Vec2::new(x, y);
+                    // Moving up or right increases the value
+                    let (delta, length) = match orientation {
+                        VERTICAL => (-delta.y, rect.h),
+                        HORIZONTAL => (delta.x, rect.w),
+                    };
+                    let pixels_per_range = match self.relative {
+                        true => self.sensitivity,
+                        false => length,
+                    };
+                    let scalar = match cx.modifiers.contains(Modifiers::SHIFT) {
+                        true => self.fine_scalar,
+                        false => 1f32,
+                    };
+                    self.drag_value += delta / pixels_per_range * scalar;
+                    // Let the value catch up with the cursor after it leaves
+                    // the slider, unless the drag isn't tied to the cursor
+                    if self.relative {
+                        self.drag_value = self.drag_value.clamp(0f32, 1f32);
+                    }
+
                     if let Some(callback) = &self.on_changing {
-                        // determine whether we are reacting to a vertical
-                        // or horizontal slider
-                        let rect = cx.cache.get_bounds(cx.current());
-                        let orientation = rect.h > rect.w;
-                        // let scalar = match
-                        //     cx.modifiers.contains(Modifiers::SHIFT) {
-                        //     true => 0.1, false => 1f32, };
-                        let mut val = {
-                            // let mapped = rect.map_ui_point((x, y).into(),
-                            // true);
-                            let ratio = rect.map_ui_point_unbounded((x, y).into(), true);
-                            self.offset
-                                + match orientation {
-                                    VERTICAL => ratio.y,
-                                    HORIZONTAL => ratio.x,
-                                }
-                        };
-
-                        // TODO: Determine scalar based on size
-
-                        // let delta_scaled = delta * scalar; Scale the
-                        // value to just the small area of our widget let
-                        // mut new_val = delta_scaled + self.value.get(cx);
-
-                        // special checks for ranges of negative width
-                        if self.range.width().signum() == -1f32 {
-                            if val > *self.range.start() {
-                                val = *self.range.start();
-                            } else if val < *self.range.end() {
-                                val = *self.range.end();
-                            }
-                        } else {
-                            val = val.clamp(*self.range.start(), *self.range.end());
-                        }
-
-                        (callback)(cx, val);
+                        let normalized = self.drag_value.clamp(0f32, 1f32);
+                        (callback)(cx, self.param.denormalize(normalized));
                     }
                 }
             }
@@ -177,26 +351,48 @@ where
         // determine whether we are drawing a vertical or horizontal slider
         let orientation = rect.h > rect.w;
 
-        self.value.view(cx.data().unwrap(), |value| {
-            match orientation {
-                VERTICAL => {
-                    let old_height = rect.h;
-                    rect.h = rect.height() * self.range.map(value.cloned().unwrap_or_default());
-                    // A little trick since values start from the top and we
-                    // want the slider to start at the bottom and go up
-                    rect.y += old_height - rect.h;
-                }
-                HORIZONTAL => {
-                    rect.w = rect.width() * self.range.map(value.cloned().unwrap_or_default())
-                }
-            }
+        let normalized = self.value.view(cx.data().unwrap(), |value| {
+            self.param.normalize(value.cloned().unwrap_or_default())
         });
+        match orientation {
+            VERTICAL => {
+                let old_height = rect.h;
+                rect.h = rect.height() * normalized;
+                // A little trick since values start from the top and we
+                // want the slider to start at the bottom and go up
+                rect.y += old_height - rect.h;
+            }
+            HORIZONTAL => rect.w = rect.width() * normalized,
+        }
 
         // Draw bar background
         let mut path = Path::new();
         path.rect(rect.x, rect.y, rect.w, rect.h);
         canvas.fill_path(&mut path, &Paint::color(background_color.into()));
 
+        // Mark the default value
+        if let Some(default) = self.param.default {
+            let bounds = cx.bounds();
+            let position = self.param.normalize(default);
+            let mut path = Path::new();
+            match orientation {
+                VERTICAL => {
+                    let y = bounds.bottom() - bounds.h * position;
+                    path.move_to(bounds.left(), y);
+                    path.line_to(bounds.right(), y);
+                }
+                HORIZONTAL => {
+                    let x = bounds.left() + bounds.w * position;
+                    path.move_to(x, bounds.top());
+                    path.line_to(x, bounds.bottom());
+                }
+            }
+            canvas.stroke_path(
+                &mut path,
+                &Paint::color(active_color.into()).with_line_width(1f32),
+            );
+        }
+
         // Draw bar line control
         let mut path = Path::new();
         let bar_thickness = if self.active {
@@ -229,14 +425,21 @@ impl<L> SliderBar<L>
 where
     L: Lens<Target = f32>,
 {
-    fn new(cx: &mut Context, value: L, range: RangeInclusive<f32>) -> Handle<Self> {
+    fn new(cx: &mut Context, value: L, param: Parameter) -> Handle<Self> {
         Self {
             value,
             on_changing: None,
-            range,
+            param,
             hover: false,
             active: false,
-            offset: 0f32,
+            relative: false,
+            sensitivity: DEFAULT_SENSITIVITY,
+            fine_scalar: DEFAULT_FINE_SCALAR,
+            drag_position: Vec2::ZERO,
+            drag_value: 0f32,
+            on_reset: None,
+            on_drag_start: None,
+            on_drag_end: None,
         }
         .build(cx, |_| {})
     }
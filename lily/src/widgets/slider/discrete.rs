@@ -0,0 +1,247 @@
+use crate::util::{end_drag, modify_view, start_drag, BoundingBoxExt, DragCallback, Steps};
+use glam::Vec2;
+use lily_derive::Handle;
+use std::collections::HashMap;
+use vizia::prelude::*;
+use vizia::vg::{Paint, Path};
+
+const VERTICAL: bool = true;
+const HORIZONTAL: bool = false;
+
+/// The length in pixels of tick marks
+const TICK_LENGTH: f32 = 4f32;
+
+/// A slider that snaps between a fixed number of options, such as the
+/// waveforms of an oscillator. It shares the `slider` element and its `.bar`
+/// styling with [`DragSlider`](super::DragSlider).
+#[derive(Handle)]
+pub struct DiscreteSlider<L>
+where
+    L: Lens<Target = usize>,
+{
+    /// A [`Lens`] of the index of the selected option
+    value: L,
+    steps: Steps,
+    hover: bool,
+    active: bool,
+    classes: HashMap<&'static str, Entity>,
+    #[callback(usize)]
+    on_changing: Option<Box<dyn Fn(&mut EventContext, usize)>>,
+    #[callback]
+    on_drag_start: DragCallback,
+    #[callback]
+    on_drag_end: DragCallback,
+}
+
+impl<L> DiscreteSlider<L>
+where
+    L: Lens<Target = usize>,
+{
+    /// Create a new `DiscreteSlider`. Depending on if dimensions are portrait
+    /// or landscape, it will automatically choose horizontal or vertical mode
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - Vizia `Context`
+    /// * `value` - a `vizia::Lens` specifying the index of the selected option
+    /// * `steps` - the options of this slider, either a number of steps like
+    ///   `4` or a list of labels like `["sine", "square", "saw"]`. Labels are
+    ///   laid out along horizontal sliders.
+    pub fn new(cx: &mut Context, value: L, steps: impl Into<Steps>) -> Handle<Self> {
+        let steps = steps.into();
+        let mut classes = HashMap::<&'static str, Entity>::default();
+        let handle = Self {
+            value,
+            steps: steps.clone(),
+            hover: false,
+            active: false,
+            classes: HashMap::default(),
+            on_changing: None,
+            on_drag_start: None,
+            on_drag_end: None,
+        }
+        .build(cx, |cx| {
+            // Built within the slider so that it is styled like `slider .bar`
+            let e = Element::new(cx).class("bar").display(Display::None).entity;
+            classes.insert("bar", e);
+            if let Steps::Labels(labels) = &steps {
+                HStack::new(cx, |cx| {
+                    for label in labels {
+                        Label::new(cx, label)
+                            .width(Stretch(1f32))
+                            .height(Stretch(1f32))
+                            .class("label");
+                    }
+                })
+                .class("labels");
+            }
+        });
+        modify_view(handle.cx, handle.entity, |slider: &mut Self| {
+            slider.classes = classes
+        });
+        handle
+    }
+
+    /// Gets the option under the cursor at `(x, y)`
+    fn index_at(&self, cx: &EventContext, x: f32, y: f32) -> usize {
+        let rect = cx.cache.get_bounds(cx.current());
+        let mapped = rect.map_ui_point(Vec2::new(x, y), false);
+        match rect.h > rect.w {
+            // Values start from the bottom
+            VERTICAL => self.steps.index_at(1f32 - mapped.y),
+            HORIZONTAL => self.steps.index_at(mapped.x),
+        }
+    }
+
+    /// Calls `on_changing` if `index` is a different option
+    fn change(&self, cx: &mut EventContext, index: usize) {
+        if index == self.value.get(cx) || index >= self.steps.len() {
+            return;
+        }
+        if let Some(callback) = &self.on_changing {
+            (callback)(cx, index);
+        }
+    }
+}
+
+impl<L> View for DiscreteSlider<L>
+where
+    L: Lens<Target = usize>,
+{
+    fn element(&self) -> Option<&'static str> {
+        Some("slider")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|ev: &WindowEvent, meta| match *ev {
+            WindowEvent::MouseEnter => self.hover = true,
+            WindowEvent::MouseLeave => self.hover = false,
+            WindowEvent::MouseDown(button) => {
+                if button == MouseButton::Left {
+                    // Receive keyboard events for stepping
+                    cx.focus();
+                    start_drag(cx, &mut self.active, &self.on_drag_start);
+                    let index = self.index_at(cx, cx.mouse.cursorx, cx.mouse.cursory);
+                    self.change(cx, index);
+                }
+            }
+            WindowEvent::MouseUp(button) => {
+                if button == MouseButton::Left {
+                    end_drag(cx, &mut self.active, &self.on_drag_end);
+                }
+            }
+            // Snap to the option under the cursor
+            WindowEvent::MouseMove(x, y) => {
+                if self.active {
+                    let index = self.index_at(cx, x, y);
+                    self.change(cx, index);
+                }
+            }
+            // Step through the options with the arrow keys
+            WindowEvent::KeyDown(code, _) => {
+                let value = self.value.get(cx);
+                let index = match code {
+                    Code::ArrowLeft | Code::ArrowDown => value.saturating_sub(1),
+                    Code::ArrowRight | Code::ArrowUp => value + 1,
+                    Code::Home => 0,
+                    Code::End => self.steps.len().saturating_sub(1),
+                    _ => return,
+                };
+                self.change(cx, index);
+                meta.consume();
+            }
+            _ => (),
+        });
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let background_color = cx.background_color().cloned().unwrap_or_default();
+        let bar_entity = *self.classes.get("bar").unwrap();
+        let fill_color = cx
+            .style
+            .background_color
+            .get(bar_entity)
+            .cloned()
+            .unwrap_or_default();
+        let active_color = cx
+            .style
+            .border_color
+            .get(bar_entity)
+            .cloned()
+            .unwrap_or_default();
+
+        let rect = cx.bounds();
+        // determine whether we are drawing a vertical or horizontal slider
+        let orientation = rect.h > rect.w;
+
+        // Draw the background
+        let mut path = Path::new();
+        path.rect(rect.x, rect.y, rect.w, rect.h);
+        canvas.fill_path(&mut path, &Paint::color(background_color.into()));
+
+        // Fill up to the selected option
+        let position = self.value.view(cx.data().unwrap(), |value| {
+            self.steps.position(value.cloned().unwrap_or_default())
+        });
+        let mut fill = rect;
+        match orientation {
+            VERTICAL => {
+                fill.h = rect.h * position;
+                fill.y += rect.h - fill.h;
+            }
+            HORIZONTAL => fill.w = rect.w * position,
+        }
+        let mut path = Path::new();
+        path.rect(fill.x, fill.y, fill.w, fill.h);
+        canvas.fill_path(&mut path, &Paint::color(fill_color.into()));
+
+        // Draw a tick for every option
+        let mut path = Path::new();
+        for index in 0..self.steps.len() {
+            let position = self.steps.position(index);
+            match orientation {
+                VERTICAL => {
+                    let y = rect.bottom() - rect.h * position;
+                    path.move_to(rect.left(), y);
+                    path.line_to(rect.left() + TICK_LENGTH, y);
+                }
+                HORIZONTAL => {
+                    let x = rect.left() + rect.w * position;
+                    path.move_to(x, rect.bottom() - TICK_LENGTH);
+                    path.line_to(x, rect.bottom());
+                }
+            }
+        }
+        canvas.stroke_path(
+            &mut path,
+            &Paint::color(active_color.into()).with_line_width(1f32),
+        );
+
+        // Draw bar line control
+        let mut path = Path::new();
+        let bar_thickness = if self.active {
+            6f32
+        } else if self.hover {
+            4f32
+        } else {
+            2f32
+        };
+
+        match orientation {
+            VERTICAL => path.rect(
+                fill.left(),
+                fill.top() - (bar_thickness / 2f32),
+                fill.width(),
+                bar_thickness,
+            ),
+            HORIZONTAL => path.rect(
+                fill.right() - (bar_thickness / 2f32),
+                fill.top(),
+                bar_thickness,
+                fill.height(),
+            ),
+        };
+
+        canvas.fill_path(&mut path, &Paint::color(active_color.into()));
+    }
+}
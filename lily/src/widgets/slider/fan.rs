@@ -0,0 +1,381 @@
+use crate::util::{end_drag, modify_view, start_drag, DragCallback, RangeExt};
+use glam::Vec2;
+use lily_derive::Handle;
+use std::{collections::HashMap, f32::consts::PI, ops::RangeInclusive};
+use vizia::prelude::*;
+use vizia::vg::{Paint, Path, Solidity};
+
+/// The number of values fanned out around the cursor
+const FAN_COUNT: usize = 9;
+/// The normalized distance between neighbouring values in the fan
+const FAN_SPREAD: f32 = 0.05;
+/// The distance in pixels from the press point to the fanned values
+const FAN_RADIUS: f32 = 48f32;
+/// The distance in pixels the cursor must move from the press point before a
+/// value is picked
+const DEAD_ZONE: f32 = 12f32;
+/// The width and height in pixels of the overlay the fan is drawn in, enough
+/// for the longest tick past the radius
+const FAN_SIZE: f32 = (FAN_RADIUS + 20f32) * 2f32;
+/// Keeps the fan above the widgets around the slider, as it is much larger
+/// than the slider itself
+const FAN_Z_ORDER: i32 = 100;
+
+/// A compact slider that fans out a set of values around the cursor when
+/// pressed. Moving the cursor towards a value picks it, so precise values can
+/// be chosen from a control with a tiny footprint. Releasing the cursor near
+/// where it was pressed keeps the original value.
+#[derive(Handle)]
+pub struct FanSlider<L>
+where
+    L: Lens<Target = f32>,
+{
+    value: L,
+    range: RangeInclusive<f32>,
+    hover: bool,
+    active: bool,
+    /// Where the fan was opened and the values in it, while pressed
+    fan: Option<(Vec2, Vec<f32>)>,
+    /// The value before the fan was opened
+    original: f32,
+    /// The index of the picked value in the fan
+    selected: Option<usize>,
+    classes: HashMap<&'static str, Entity>,
+    #[callback(f32)]
+    on_changing: Option<Box<dyn Fn(&mut EventContext, f32)>>,
+    #[callback]
+    on_drag_start: DragCallback,
+    #[callback]
+    on_drag_end: DragCallback,
+}
+
+impl<L> FanSlider<L>
+where
+    L: Lens<Target = f32>,
+{
+    /// Create a new `FanSlider`
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - Vizia `Context`
+    /// * `value` - a `vizia::Lens` specifying the value of this slider
+    /// * `range` - the arbitrary range of this slider
+    pub fn new(cx: &mut Context, value: L, range: RangeInclusive<f32>) -> Handle<Self> {
+        let mut classes = HashMap::<&'static str, Entity>::default();
+        let handle = Self {
+            value,
+            range: range.clone(),
+            hover: false,
+            active: false,
+            fan: None,
+            original: 0f32,
+            selected: None,
+            classes: HashMap::default(),
+            on_changing: None,
+            on_drag_start: None,
+            on_drag_end: None,
+        }
+        .build(cx, |cx| {
+            // Built within the slider so that it is styled by the theme
+            let e = Element::new(cx).class("bar").display(Display::None).entity;
+            classes.insert("bar", e);
+
+            // The fan is drawn in its own view on top of everything else,
+            // rather than by the slider, so that it isn't clipped to the
+            // slider or painted over by its neighbours
+            FanData { fan: None }.build(cx);
+            Binding::new(cx, FanData::fan, move |cx, fan| {
+                if let Some(fan) = fan.get(cx) {
+                    let corner = fan.origin - FAN_SIZE / 2f32;
+                    FanOverlay {
+                        range: range.clone(),
+                        fan,
+                    }
+                    .build(cx, |_| {})
+                    .position_type(PositionType::SelfDirected)
+                    .left(Pixels(corner.x))
+                    .top(Pixels(corner.y))
+                    .width(Pixels(FAN_SIZE))
+                    .height(Pixels(FAN_SIZE))
+                    .z_order(FAN_Z_ORDER);
+                }
+            });
+        });
+        modify_view(handle.cx, handle.entity, |slider: &mut Self| {
+            slider.classes = classes
+        });
+        handle
+    }
+}
+
+impl<L> View for FanSlider<L>
+where
+    L: Lens<Target = f32>,
+{
+    fn element(&self) -> Option<&'static str> {
+        Some("slider")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|ev: &WindowEvent, _| match *ev {
+            WindowEvent::MouseEnter => self.hover = true,
+            WindowEvent::MouseLeave => self.hover = false,
+            WindowEvent::MouseDown(button) => {
+                if button == MouseButton::Left {
+                    let value = self.value.get(cx);
+                    let origin = Vec2::new(cx.mouse.cursorx, cx.mouse.cursory);
+                    let values = fan_values(&self.range, value, FAN_COUNT, FAN_SPREAD);
+                    self.original = value;
+                    self.selected = None;
+                    self.fan = Some((origin, values.clone()));
+                    // The overlay is positioned relative to the slider
+                    let bounds = cx.cache.get_bounds(cx.current());
+                    cx.emit(FanEvent::Open(Fan {
+                        origin: origin - Vec2::new(bounds.x, bounds.y),
+                        values,
+                        selected: None,
+                    }));
+                    start_drag(cx, &mut self.active, &self.on_drag_start);
+                }
+            }
+            WindowEvent::MouseUp(button) => {
+                if button == MouseButton::Left {
+                    self.fan = None;
+                    self.selected = None;
+                    cx.emit(FanEvent::Close);
+                    end_drag(cx, &mut self.active, &self.on_drag_end);
+                }
+            }
+            // Pick the value the cursor points towards
+            WindowEvent::MouseMove(x, y) => {
+                if let Some((origin, values)) = &self.fan {
+                    let selected = fan_index(*origin, Vec2::new(x, y), values.len(), DEAD_ZONE);
+                    if selected != self.selected {
+                        self.selected = selected;
+                        cx.emit(FanEvent::Select(selected));
+                        let value = selected.map(|i| values[i]).unwrap_or(self.original);
+                        if let Some(callback) = &self.on_changing {
+                            (callback)(cx, value);
+                        }
+                    }
+                }
+            }
+            _ => (),
+        });
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let background_color = cx.background_color().cloned().unwrap_or_default();
+        let bar_entity = *self.classes.get("bar").unwrap();
+        let fill_color = cx
+            .style
+            .background_color
+            .get(bar_entity)
+            .cloned()
+            .unwrap_or_default();
+        let active_color = cx
+            .style
+            .border_color
+            .get(bar_entity)
+            .cloned()
+            .unwrap_or_default();
+
+        // Draw the value like a horizontal slider
+        let rect = cx.bounds();
+        let mut path = Path::new();
+        path.rect(rect.x, rect.y, rect.w, rect.h);
+        canvas.fill_path(&mut path, &Paint::color(background_color.into()));
+
+        let value = self.value.view(cx.data().unwrap(), |value| {
+            self.range.map(value.cloned().unwrap_or_default())
+        });
+        let width = rect.w * value.clamp(0f32, 1f32);
+        let mut path = Path::new();
+        path.rect(rect.x, rect.y, width, rect.h);
+        canvas.fill_path(&mut path, &Paint::color(fill_color.into()));
+
+        let bar_thickness = match self.hover || self.fan.is_some() {
+            true => 4f32,
+            false => 2f32,
+        };
+        let mut path = Path::new();
+        path.rect(
+            rect.x + width - bar_thickness / 2f32,
+            rect.y,
+            bar_thickness,
+            rect.h,
+        );
+        canvas.fill_path(&mut path, &Paint::color(active_color.into()));
+    }
+}
+
+/// The fan opened by pressing a [`FanSlider`], kept in a model so that the
+/// overlay drawing it is rebuilt as it changes
+#[derive(Lens)]
+struct FanData {
+    fan: Option<Fan>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Fan {
+    /// The press point, relative to the slider
+    origin: Vec2,
+    values: Vec<f32>,
+    /// The index of the picked value
+    selected: Option<usize>,
+}
+
+impl Data for Fan {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+enum FanEvent {
+    Open(Fan),
+    Select(Option<usize>),
+    Close,
+}
+
+impl Model for FanData {
+    fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+        event.map(|ev: &FanEvent, _| match ev {
+            FanEvent::Open(fan) => self.fan = Some(fan.clone()),
+            FanEvent::Select(selected) => {
+                if let Some(fan) = &mut self.fan {
+                    fan.selected = *selected;
+                }
+            }
+            FanEvent::Close => self.fan = None,
+        });
+    }
+}
+
+/// Draws a [`Fan`] centred within its bounds
+struct FanOverlay {
+    range: RangeInclusive<f32>,
+    fan: Fan,
+}
+
+impl View for FanOverlay {
+    fn element(&self) -> Option<&'static str> {
+        Some("fan")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let point_color = cx.border_color().cloned().unwrap_or_default();
+        let selected_color = cx.background_color().cloned().unwrap_or_default();
+        let rect = cx.bounds();
+        let origin = Vec2::new(rect.x + rect.w / 2f32, rect.y + rect.h / 2f32);
+
+        // Draw the values around the press point, each value shown as a tick
+        // whose length is its position within the range
+        let mut path = Path::new();
+        path.arc(origin.x, origin.y, FAN_RADIUS, PI, 0f32, Solidity::Hole);
+        canvas.stroke_path(
+            &mut path,
+            &Paint::color(point_color.into()).with_line_width(1f32),
+        );
+        let values = &self.fan.values;
+        for (i, value) in values.iter().enumerate() {
+            let direction = fan_direction(i, values.len());
+            let length = 4f32 + self.range.map(*value).clamp(0f32, 1f32) * 12f32;
+            let inner = origin + direction * FAN_RADIUS;
+            let outer = inner + direction * length;
+            let (color, width) = match self.fan.selected == Some(i) {
+                true => (selected_color, 4f32),
+                false => (point_color, 2f32),
+            };
+            let mut path = Path::new();
+            path.move_to(inner.x, inner.y);
+            path.line_to(outer.x, outer.y);
+            canvas.stroke_path(
+                &mut path,
+                &Paint::color(color.into()).with_line_width(width),
+            );
+        }
+    }
+}
+
+/// Gets `count` values centred around `value`, each `spread` apart in
+/// normalized space and kept within `range`
+pub(crate) fn fan_values(
+    range: &RangeInclusive<f32>,
+    value: f32,
+    count: usize,
+    spread: f32,
+) -> Vec<f32> {
+    let center = range.map(value);
+    let middle = count.saturating_sub(1) as f32 / 2f32;
+    (0..count)
+        .map(|i| {
+            let normalized = (center + (i as f32 - middle) * spread).clamp(0f32, 1f32);
+            range.start() + normalized * range.width()
+        })
+        .collect()
+}
+
+/// Gets the direction from the press point to the value at `index` of a fan
+/// of `count` values, spread over the upper half circle from left to right
+fn fan_direction(index: usize, count: usize) -> Vec2 {
+    let t = match count {
+        0 | 1 => 0.5,
+        _ => index as f32 / (count - 1) as f32,
+    };
+    // Angles go clockwise in UI space, so the upper half is from PI to 2 PI
+    let angle = PI + t * PI;
+    Vec2::new(angle.cos(), angle.sin())
+}
+
+/// Gets the index of the value in a fan of `count` values that `cursor`
+/// points towards from `origin`, or `None` if the cursor is within
+/// `dead_zone` pixels of the origin
+pub(crate) fn fan_index(origin: Vec2, cursor: Vec2, count: usize, dead_zone: f32) -> Option<usize> {
+    let delta = cursor - origin;
+    if count == 0 || delta.length() < dead_zone {
+        return None;
+    }
+    // The angle from the right, going anticlockwise over the top. Pointing
+    // below the origin picks the closest end of the fan.
+    let angle = (-delta.y).atan2(delta.x);
+    let t = match angle < 0f32 {
+        true if delta.x < 0f32 => 0f32,
+        true => 1f32,
+        false => 1f32 - angle / PI,
+    };
+    Some((t.clamp(0f32, 1f32) * (count - 1) as f32).round() as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn gets_fan_values() {
+        let values = fan_values(&(0f32..=10f32), 5f32, 5, 0.1);
+        for (value, expected) in values.iter().zip([3f32, 4f32, 5f32, 6f32, 7f32]) {
+            assert_approx_eq!(*value, expected);
+        }
+        // Values stay within the range
+        let values = fan_values(&(0f32..=10f32), 0.5, 3, 0.1);
+        assert_approx_eq!(values[0], 0f32);
+        assert_approx_eq!(values[2], 1.5);
+    }
+
+    #[test]
+    fn gets_fan_index() {
+        let origin = Vec2::new(100f32, 100f32);
+        let index = |x: f32, y: f32| fan_index(origin, Vec2::new(x, y), 5, 10f32);
+        assert_eq!(index(102f32, 101f32), None);
+        // Left, up and right
+        assert_eq!(index(50f32, 100f32), Some(0));
+        assert_eq!(index(100f32, 50f32), Some(2));
+        assert_eq!(index(150f32, 100f32), Some(4));
+        // Up and to the left
+        assert_eq!(index(60f32, 60f32), Some(1));
+        // Below the origin picks an end
+        assert_eq!(index(90f32, 150f32), Some(0));
+        assert_eq!(index(110f32, 150f32), Some(4));
+    }
+}
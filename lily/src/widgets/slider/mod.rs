@@ -1,2 +1,6 @@
 mod continuous;
-pub use continuous::{DragSlider, DragSliderHandle};
+mod discrete;
+mod fan;
+pub use continuous::{DragSlider, DragSliderHandle, DragSliderModifiers};
+pub use discrete::{DiscreteSlider, DiscreteSliderHandle};
+pub use fan::{FanSlider, FanSliderHandle};
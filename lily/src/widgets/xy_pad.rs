@@ -3,9 +3,9 @@ use std::collections::HashMap;
 use glam::Vec2;
 use lily_derive::Handle;
 use vizia::prelude::*;
-use vizia::vg;
+use vizia::vg::{Paint, Path};
 
-use crate::util::BoundingBoxExt;
+use crate::util::{modify_view, BoundingBoxExt, DragCallback};
 
 /// Controls a single point along a normalized XY axis `(-1,-1)..=(1,1)`.
 #[derive(Handle)]
@@ -19,7 +19,13 @@ where
     // Temporary workaround until we can get custom css stuff directly
     classes: HashMap<&'static str, Entity>,
     #[callback(Vec2)]
-    on_changing_point: Option<Box<dyn Fn(&mut Context, Vec2)>>,
+    on_changing_point: Option<Box<dyn Fn(&mut EventContext, Vec2)>>,
+    /// Called when the point is grabbed, before any changes
+    #[callback]
+    on_drag_start: DragCallback,
+    /// Called when the point is released
+    #[callback]
+    on_drag_end: DragCallback,
 }
 
 #[derive(PartialEq, Eq, Clone, Copy)]
@@ -35,20 +41,25 @@ where
 {
     pub fn new(cx: &mut Context, point: P) -> Handle<Self> {
         let mut classes = HashMap::<&'static str, Entity>::default();
-        let mut insert_color = |name| {
-            let e = Element::new(cx).class(name).display(Display::None).entity;
-            classes.insert(name, e);
-        };
-        insert_color("point");
-        insert_color("crosshair");
-        Self {
+        let handle = Self {
             point,
             on_changing_point: None,
+            on_drag_start: None,
+            on_drag_end: None,
             state: InternalState::NoOp,
-            classes,
+            classes: HashMap::default(),
             offset: Vec2::ZERO,
         }
-        .build(cx, |_| {})
+        .build(cx, |cx| {
+            for name in ["point", "crosshair"] {
+                let e = Element::new(cx).class(name).display(Display::None).entity;
+                classes.insert(name, e);
+            }
+        });
+        modify_view(handle.cx, handle.entity, |pad: &mut Self| {
+            pad.classes = classes
+        });
+        handle
     }
 }
 
@@ -88,32 +99,35 @@ where
                 }
             }
             WindowEvent::MouseDown(button) => {
-                if button == MouseButton::Left {
+                if button == MouseButton::Left && self.state == InternalState::Hovering {
                     cx.capture();
-                    if self.state == InternalState::Hovering {
-                        self.state = InternalState::Dragging;
-                        // Set the offset
-                        let rect = cx.cache.get_bounds(cx.current);
-                        let cursor_pos_scaled = rect.map_ui_point_unbounded(
-                            (cx.mouse.cursorx, cx.mouse.cursory).into(),
-                            true,
-                        );
-                        self.offset = self.point.get(cx) - cursor_pos_scaled;
+                    self.state = InternalState::Dragging;
+                    // Keep the distance between the cursor and the point so
+                    // that the point doesn't jump to the cursor
+                    let rect = cx.cache.get_bounds(cx.current());
+                    let cursor_pos_scaled = rect.map_ui_point_unbounded(
+                        Vec2::new(cx.mouse.cursorx, cx.mouse.cursory),
+                        true,
+                    );
+                    self.offset = self.point.get(cx) - cursor_pos_scaled;
+                    if let Some(callback) = &self.on_drag_start {
+                        (callback)(cx);
                     }
                 }
             }
             WindowEvent::MouseUp(button) => {
-                let cursor_pos: Vec2 = (cx.mouse.cursorx, cx.mouse.cursory).into();
-                let rect = cx.cache.get_bounds(cx.current);
-
-                if button == MouseButton::Left {
+                if button == MouseButton::Left && self.state == InternalState::Dragging {
+                    let cursor_pos = Vec2::new(cx.mouse.cursorx, cx.mouse.cursory);
+                    let rect = cx.cache.get_bounds(cx.current());
                     cx.release();
-                    self.offset = Vec2::ZERO;
-                    self.state = if rect.contains_point(cursor_pos) {
-                        InternalState::Hovering
-                    } else {
-                        InternalState::NoOp
+                    if let Some(callback) = &self.on_drag_end {
+                        (callback)(cx);
                     }
+                    self.offset = Vec2::ZERO;
+                    self.state = match rect.contains_point(cursor_pos) {
+                        true => InternalState::Hovering,
+                        false => InternalState::NoOp,
+                    };
                 }
             }
             _ => (),
@@ -122,10 +136,31 @@ where
 
     fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
         let rect = cx.bounds();
-        let bg = cx.background_color().copied().unwrap_or_default();
-        let border = cx.border_color().copied().unwrap_or_default();
+        let bg = cx.background_color().cloned().unwrap_or_default();
+        let border = cx.border_color().cloned().unwrap_or_default();
+
+        // Get custom CSS info from display none elements
+        let point_entity = *self.classes.get("point").unwrap();
+        let point_color = cx
+            .style
+            .background_color
+            .get(point_entity)
+            .cloned()
+            .unwrap_or_default();
+        let point_border = cx
+            .style
+            .border_color
+            .get(point_entity)
+            .cloned()
+            .unwrap_or_default();
+        let crosshair_entity = *self.classes.get("crosshair").unwrap();
+        let crosshair_color = cx
+            .style
+            .border_color
+            .get(crosshair_entity)
+            .cloned()
+            .unwrap_or_default();
 
-        // Draw background shapes
         // Background
         let mut path = Path::new();
         path.rect(rect.x, rect.y, rect.w, rect.h);
@@ -148,54 +183,37 @@ where
         for scale in [1.0, 0.66, 0.33] {
             path.circle(center_x, center_y, (rect.w / 2f32) * scale);
         }
-        canvas.stroke_path(&mut path, Paint::color(border.into()));
+        canvas.stroke_path(&mut path, &Paint::color(border.into()));
 
         // Data point
-        self.point.view(cx.data().unwrap(), |point| {
-            let point = *point.unwrap();
-            let point_entity = *self.classes.get("point").unwrap();
-            let ui_point = rect.map_data_point(point, true);
-            let point_border = cx.border_color(point_entity).cloned().unwrap_or_default();
-            let point_color = cx
-                .background_color(point_entity)
-                .cloned()
-                .unwrap_or_default();
-
-            // Draw crosshairs when dragging
-            let crosshair_entity = *self.classes.get("crosshair").unwrap();
-            let crosshair_color = cx
-                .border_color(crosshair_entity)
-                .cloned()
-                .unwrap_or_default();
-            if self.state == InternalState::Dragging {
-                let mut path = Path::new();
-                path.move_to(ui_point.x, rect.top());
-                path.line_to(ui_point.x, rect.bottom());
-                path.move_to(rect.left(), ui_point.y);
-                path.line_to(rect.right(), ui_point.y);
-                canvas.stroke_path(&mut path, Paint::color(crosshair_color.into()));
-            }
-
-            // Point fill
-            let mut path = Path::new();
-            path.circle(ui_point.x, ui_point.y, 4f32);
-            canvas.fill_path(&mut path, Paint::color(point_color.into()));
+        let point = self.point.view(cx.data().unwrap(), |point| {
+            point.cloned().unwrap_or_default()
+        });
+        let ui_point = rect.map_data_point(point, true);
 
-            // Point outline
+        // Draw crosshairs when dragging
+        if self.state == InternalState::Dragging {
             let mut path = Path::new();
-            match self.state {
-                InternalState::Dragging | InternalState::Hovering => {
-                    path.circle(ui_point.x, ui_point.y, 8f32)
-                }
-                _ => (),
-            }
+            path.move_to(ui_point.x, rect.top());
+            path.line_to(ui_point.x, rect.bottom());
+            path.move_to(rect.left(), ui_point.y);
+            path.line_to(rect.right(), ui_point.y);
+            canvas.stroke_path(&mut path, &Paint::color(crosshair_color.into()));
+        }
 
-            // Get custom CSS info from a display none element
+        // Point fill
+        let mut path = Path::new();
+        path.circle(ui_point.x, ui_point.y, 4f32);
+        canvas.fill_path(&mut path, &Paint::color(point_color.into()));
 
+        // Point outline when hovering or dragging
+        if self.state != InternalState::NoOp {
+            let mut path = Path::new();
+            path.circle(ui_point.x, ui_point.y, 8f32);
             canvas.stroke_path(
                 &mut path,
-                Paint::color(point_border.into()).with_line_width(2f32),
+                &Paint::color(point_border.into()).with_line_width(2f32),
             );
-        });
+        }
     }
 }
@@ -1,7 +1,9 @@
 use std::ops::RangeInclusive;
 
+use crate::util::DragCallback;
 use glam::Vec2;
 use lily_derive::Handle;
+use vizia::cache::BoundingBox;
 use vizia::prelude::*;
 use vizia::vg::{Paint, Path};
 // use vizia::{
@@ -10,7 +12,10 @@ use vizia::vg::{Paint, Path};
 // };
 
 const HANDLE_SIZE: f32 = 16.0;
-const SMALLEST_RANGE: f32 = 0.1;
+/// The thickness in pixels of a zoomer across its bar
+pub(crate) const ZOOMER_SIZE: f32 = 24f32;
+/// The smallest width the zoomed range can have
+pub(crate) const SMALLEST_RANGE: f32 = 0.1;
 
 #[allow(clippy::type_complexity)]
 #[derive(Handle)]
@@ -20,12 +25,26 @@ where
 {
     range: R,
     status: ZoomerEvent,
+    /// The distance from the start of the range to the cursor when the bar
+    /// was grabbed, so that the bar doesn't jump to the cursor
+    grab_offset: f32,
+    /// Whether the zoomer runs from the bottom to the top instead of from
+    /// left to right
+    vertical: bool,
+    /// Draws an overview of the full content behind the bar
+    content: Option<Box<dyn Fn(&mut DrawContext, &mut Canvas, BoundingBox)>>,
     #[callback(f32, f32)]
     on_changing_both: Option<Box<dyn Fn(&mut EventContext, f32, f32)>>,
     #[callback(f32)]
     on_changing_end: Option<Box<dyn Fn(&mut EventContext, f32)>>,
     #[callback(f32)]
     on_changing_start: Option<Box<dyn Fn(&mut EventContext, f32)>>,
+    /// Called when the bar or a handle is grabbed, before any changes
+    #[callback]
+    on_drag_start: DragCallback,
+    /// Called when the bar or handle is released
+    #[callback]
+    on_drag_end: DragCallback,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -57,19 +76,39 @@ impl View for ZoomerControl {
         // Fill the quad
         canvas.fill_path(&mut path, &paint);
 
-        // paint some grabby lines
-        let height_offset = bounds.h / 6f32;
-        let third = bounds.w / 3f32;
-        let x1 = bounds.x + third;
-        let line_1 = [
-            Vec2::new(x1, bounds.y + height_offset),
-            Vec2::new(x1, bounds.y + bounds.h - height_offset),
-        ];
-        let x2 = bounds.x + (third * 2f32);
-        let line_2 = [
-            Vec2::new(x2, bounds.y + height_offset),
-            Vec2::new(x2, bounds.y + bounds.h - height_offset),
-        ];
+        // paint some grabby lines, running along the longer side so they
+        // suit both horizontal and vertical zoomers
+        let (line_1, line_2) = if bounds.h >= bounds.w {
+            let height_offset = bounds.h / 6f32;
+            let third = bounds.w / 3f32;
+            let x1 = bounds.x + third;
+            let x2 = bounds.x + (third * 2f32);
+            (
+                [
+                    Vec2::new(x1, bounds.y + height_offset),
+                    Vec2::new(x1, bounds.y + bounds.h - height_offset),
+                ],
+                [
+                    Vec2::new(x2, bounds.y + height_offset),
+                    Vec2::new(x2, bounds.y + bounds.h - height_offset),
+                ],
+            )
+        } else {
+            let width_offset = bounds.w / 6f32;
+            let third = bounds.h / 3f32;
+            let y1 = bounds.y + third;
+            let y2 = bounds.y + (third * 2f32);
+            (
+                [
+                    Vec2::new(bounds.x + width_offset, y1),
+                    Vec2::new(bounds.x + bounds.w - width_offset, y1),
+                ],
+                [
+                    Vec2::new(bounds.x + width_offset, y2),
+                    Vec2::new(bounds.x + bounds.w - width_offset, y2),
+                ],
+            )
+        };
 
         let mut path = Path::new();
         path.move_to(line_1[0].x, line_1[0].y);
@@ -94,17 +133,58 @@ where
     R: Lens<Target = RangeInclusive<f32>>,
 {
     pub fn new(cx: &mut Context, range: R) -> Handle<Self> {
-        Self {
+        Self::build_zoomer(cx, range, false, None)
+    }
+
+    /// Create a new `Zoomer` that runs from the bottom to the top, for zooming
+    /// the vertical axis of a graph
+    pub fn vertical(cx: &mut Context, range: R) -> Handle<Self> {
+        Self::build_zoomer(cx, range, true, None)
+    }
+
+    /// Create a new `Zoomer` that draws a miniature overview of what it zooms
+    /// behind the bar, like the overview strip of a DAW arrangement.
+    ///
+    /// # Parameters
+    ///
+    /// * `cx` - the current [`Context`]
+    /// * `range` - a [`Lens`] with a target of [`RangeInclusive<f32>`]
+    ///   representing the zoomed section, between 0 and 1 inclusive
+    /// * `content` - draws the full, unzoomed content within the given bounds
+    pub fn with_content<F>(cx: &mut Context, range: R, content: F) -> Handle<Self>
+    where
+        F: 'static + Fn(&mut DrawContext, &mut Canvas, BoundingBox),
+    {
+        Self::build_zoomer(cx, range, false, Some(Box::new(content)))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn build_zoomer(
+        cx: &mut Context,
+        range: R,
+        vertical: bool,
+        content: Option<Box<dyn Fn(&mut DrawContext, &mut Canvas, BoundingBox)>>,
+    ) -> Handle<Self> {
+        let handle = Self {
             on_changing_start: None,
             on_changing_end: None,
             on_changing_both: None,
+            on_drag_start: None,
+            on_drag_end: None,
             status: ZoomerEvent::FinishSet,
+            grab_offset: 0f32,
+            vertical,
+            content,
             range: range.clone(),
         }
         .build(cx, |cx| {
             let parent_entity = cx.current();
 
             Binding::new(cx, range.clone(), move |cx, _internal| {
+                if vertical {
+                    ZStack::new(cx, |cx| build_vertical(cx, range.clone()));
+                    return;
+                }
                 ZStack::new(cx, |cx| {
                     // Bar
                     Element::new(cx)
@@ -172,12 +252,74 @@ where
                     //     });
                 });
             });
-        })
-        .width(Stretch(1.0))
-        .height(Pixels(24f32))
+        });
+        match vertical {
+            true => handle.width(Pixels(ZOOMER_SIZE)).height(Stretch(1.0)),
+            false => handle.width(Stretch(1.0)).height(Pixels(ZOOMER_SIZE)),
+        }
+    }
+
+    /// Gets the normalized position of the cursor along the zoomer
+    fn cursor_position(&self, cx: &EventContext, x: f32, y: f32) -> f32 {
+        let bounds = cx.cache.get_bounds(cx.current());
+        match self.vertical {
+            true => (bounds.y + bounds.h - y) / bounds.h,
+            false => (x - bounds.x) / bounds.w,
+        }
     }
 }
 
+/// Builds the bar and handles of a vertical zoomer, where the start of the
+/// range is at the bottom
+fn build_vertical<R>(cx: &mut Context, range: R)
+where
+    R: Lens<Target = RangeInclusive<f32>>,
+{
+    // Bar
+    Element::new(cx)
+        .width(Stretch(1.0))
+        .class("bar")
+        .bind(range.clone(), |handle, value| {
+            let val = value.get(handle.cx);
+            let height = val.end() - val.start();
+            handle
+                .height(Percentage(height * 100.0))
+                .top(Percentage((1.0 - val.end()) * 100.0))
+                .on_press(|cx| {
+                    cx.emit(ZoomerEvent::SetBoth);
+                });
+        });
+
+    // Start handle
+    ZoomerControl::new(cx)
+        .class("handle")
+        .width(Stretch(1.0))
+        .bind(range.clone(), move |handle, value| {
+            let val = value.get(handle.cx);
+            handle
+                .top(Stretch(1f32))
+                .bottom(Percentage(*val.start() * 100.0))
+                .height(Pixels(HANDLE_SIZE))
+                .on_press(move |cx| {
+                    cx.emit(ZoomerEvent::SetStart);
+                });
+        });
+
+    // End handle
+    ZoomerControl::new(cx)
+        .class("handle")
+        .width(Stretch(1.0))
+        .bind(range, move |handle, value| {
+            let val = value.get(handle.cx);
+            handle
+                .top(Percentage(100.0 * (1.0 - val.end())))
+                .height(Pixels(HANDLE_SIZE))
+                .on_press(move |cx| {
+                    cx.emit(ZoomerEvent::SetEnd);
+                });
+        });
+}
+
 impl<R> View for Zoomer<R>
 where
     R: Lens<Target = RangeInclusive<f32>>,
@@ -188,18 +330,29 @@ where
 
     fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
         event.map(|ev: &ZoomerEvent, _| {
+            // Grabbing the bar or a handle starts a drag, which lasts until
+            // the mouse is released
+            let callback = match (self.status, ev) {
+                (ZoomerEvent::FinishSet, ZoomerEvent::FinishSet) => None,
+                (ZoomerEvent::FinishSet, _) => self.on_drag_start.as_ref(),
+                (_, ZoomerEvent::FinishSet) => self.on_drag_end.as_ref(),
+                _ => None,
+            };
+            if let Some(callback) = callback {
+                (callback)(cx);
+            }
             self.status = *ev;
+            if let ZoomerEvent::SetBoth = ev {
+                let x = self.cursor_position(cx, cx.mouse.cursorx, cx.mouse.cursory);
+                self.grab_offset = x - *self.range.get(cx).start();
+            }
         });
         #[allow(clippy::collapsible_match)]
         event.map(|ev: &WindowEvent, _| match *ev {
             // Respond to cursor movements when we are setting the start or end
-            WindowEvent::MouseMove(x, _y) => {
-                let width = cx.cache.get_width(cx.current());
+            WindowEvent::MouseMove(x, y) => {
                 let range = self.range.get(cx);
-                // adjust X to be relative
-                let mut x = x - cx.cache.get_bounds(cx.current()).x;
-                // get new data x
-                x /= width;
+                let x = self.cursor_position(cx, x, y);
                 match self.status {
                     ZoomerEvent::SetStart => {
                         // Set the zoomer amount based on the mouse positioning
@@ -215,7 +368,13 @@ where
                         }
                     }
                     ZoomerEvent::SetBoth => {
-                        // TODO:
+                        // Move the whole range with the cursor, keeping its
+                        // width when it reaches either edge
+                        let width = range.end() - range.start();
+                        let start = (x - self.grab_offset).clamp(0f32, 1f32 - width);
+                        if let Some(callback) = &self.on_changing_both {
+                            (callback)(cx, start, start + width);
+                        }
                     }
                     _ => (),
                 }
@@ -247,7 +406,11 @@ where
         let background_color = cx.background_color().cloned().unwrap_or_default();
         // Draw background rect
         let mut path = Path::new();
-        path.rect(0f32, 0f32, width, height);
+        path.rect(rect.x, rect.y, width, height);
         canvas.fill_path(&mut path, &Paint::color(background_color.into()));
+
+        if let Some(content) = &self.content {
+            (content)(cx, canvas, rect);
+        }
     }
 }